@@ -0,0 +1,15 @@
+-- CreateTable
+CREATE TABLE "AuctionEvent" (
+    "id" BIGSERIAL NOT NULL,
+    "stream_id" INTEGER NOT NULL,
+    "version" INTEGER NOT NULL,
+    "event_type" TEXT NOT NULL,
+    "payload" JSONB NOT NULL,
+    "metadata" JSONB NOT NULL DEFAULT '{}',
+    "recorded_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "AuctionEvent_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "AuctionEvent_stream_id_version_key" ON "AuctionEvent"("stream_id", "version");
@@ -0,0 +1,10 @@
+-- CreateTable
+CREATE TABLE "AuctionSnapshot" (
+    "stream_id" INTEGER NOT NULL,
+    "version" INTEGER NOT NULL,
+    "snapshot_version" INTEGER NOT NULL,
+    "state" JSONB NOT NULL,
+    "taken_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "AuctionSnapshot_pkey" PRIMARY KEY ("stream_id")
+);
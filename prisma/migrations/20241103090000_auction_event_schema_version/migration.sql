@@ -0,0 +1,2 @@
+-- AlterTable
+ALTER TABLE "AuctionEvent" ADD COLUMN "schema_version" INTEGER NOT NULL DEFAULT 1;
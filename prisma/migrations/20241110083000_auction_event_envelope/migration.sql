@@ -0,0 +1,21 @@
+-- AlterTable
+ALTER TABLE "AuctionEvent" ADD COLUMN "event_id" UUID,
+ADD COLUMN "occurred_at" TIMESTAMP(3),
+ADD COLUMN "actor_id" INTEGER,
+ADD COLUMN "correlation_id" UUID,
+ADD COLUMN "causation_id" UUID;
+
+-- Events stored before the envelope existed each start a correlation of their own
+UPDATE "AuctionEvent" SET "event_id" = gen_random_uuid(),
+"occurred_at" = "recorded_at",
+"correlation_id" = gen_random_uuid();
+
+ALTER TABLE "AuctionEvent" ALTER COLUMN "event_id" SET NOT NULL,
+ALTER COLUMN "occurred_at" SET NOT NULL,
+ALTER COLUMN "correlation_id" SET NOT NULL;
+
+-- CreateIndex
+CREATE UNIQUE INDEX "AuctionEvent_event_id_key" ON "AuctionEvent"("event_id");
+
+-- CreateIndex
+CREATE INDEX "AuctionEvent_correlation_id_idx" ON "AuctionEvent"("correlation_id");
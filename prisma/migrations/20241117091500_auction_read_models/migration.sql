@@ -0,0 +1,29 @@
+-- CreateTable
+CREATE TABLE "AuctionSummary" (
+    "auction_id" INTEGER NOT NULL,
+    "seller_id" INTEGER,
+    "status" TEXT NOT NULL,
+    "currency" TEXT,
+    "current_price" INTEGER,
+    "bid_count" INTEGER NOT NULL,
+    "ends_at" TIMESTAMP(3),
+    "version" INTEGER NOT NULL,
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "AuctionSummary_pkey" PRIMARY KEY ("auction_id")
+);
+
+-- CreateTable
+CREATE TABLE "AuctionBidHistory" (
+    "auction_id" INTEGER NOT NULL,
+    "version" INTEGER NOT NULL,
+    "bidder_id" INTEGER NOT NULL,
+    "currency" TEXT NOT NULL,
+    "price" INTEGER NOT NULL,
+    "placed_at" TIMESTAMP(3) NOT NULL,
+
+    CONSTRAINT "AuctionBidHistory_pkey" PRIMARY KEY ("auction_id","version")
+);
+
+-- CreateIndex
+CREATE INDEX "AuctionSummary_status_ends_at_idx" ON "AuctionSummary"("status", "ends_at");
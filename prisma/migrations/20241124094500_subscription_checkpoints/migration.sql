@@ -0,0 +1,8 @@
+-- CreateTable
+CREATE TABLE "SubscriptionCheckpoint" (
+    "subscriber" TEXT NOT NULL,
+    "position" BIGINT NOT NULL,
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "SubscriptionCheckpoint_pkey" PRIMARY KEY ("subscriber")
+);
@@ -0,0 +1,18 @@
+-- CreateTable
+CREATE TABLE "AuctionOutbox" (
+    "id" BIGSERIAL NOT NULL,
+    "event_id" UUID NOT NULL,
+    "stream_id" INTEGER NOT NULL,
+    "schema_version" INTEGER NOT NULL,
+    "payload" JSONB NOT NULL,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "published_at" TIMESTAMP(3),
+
+    CONSTRAINT "AuctionOutbox_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "AuctionOutbox_event_id_key" ON "AuctionOutbox"("event_id");
+
+-- CreateIndex
+CREATE INDEX "AuctionOutbox_published_at_id_idx" ON "AuctionOutbox"("published_at", "id");
@@ -0,0 +1,14 @@
+-- CreateTable
+CREATE TABLE "ModerationRecord" (
+    "id" SERIAL NOT NULL,
+    "auction_id" INTEGER NOT NULL,
+    "bidder_id" INTEGER NOT NULL,
+    "code" TEXT NOT NULL,
+    "rejection" JSONB NOT NULL,
+    "recorded_at" TIMESTAMP(3) NOT NULL,
+
+    CONSTRAINT "ModerationRecord_pkey" PRIMARY KEY ("id")
+);
+
+-- CreateIndex
+CREATE INDEX "ModerationRecord_auction_id_idx" ON "ModerationRecord"("auction_id");
@@ -0,0 +1,5 @@
+-- Event times are kept to the microsecond, as they are read back
+ALTER TABLE "AuctionEvent" ALTER COLUMN "occurred_at" SET DATA TYPE TIMESTAMP(6);
+
+-- Envelope replaces the metadata column, which was never written
+ALTER TABLE "AuctionEvent" DROP COLUMN "metadata";
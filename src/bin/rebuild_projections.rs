@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use bgapp::{
+    prisma::PrismaClient,
+    repository::{
+        postgres_auction_read_model::PostgresAuctionReadModel,
+        postgres_event_store::PostgresEventStore,
+    },
+    services::auction_projector::AuctionProjector,
+};
+
+// Drops the auction read models and projects them again from the full event store
+#[tokio::main]
+async fn main() {
+    // One client, and its connection pool, serves both the event store and the read models
+    let db_client = Arc::new(
+        PrismaClient::_builder()
+            .build()
+            .await
+            .expect("Failed to initialize db client"),
+    );
+    let event_store = PostgresEventStore::new(db_client.clone());
+    let read_model = PostgresAuctionReadModel::new(db_client);
+    let projector = AuctionProjector::new(
+        Arc::new(event_store),
+        Arc::new(read_model),
+        std::time::Duration::from_secs(1),
+    );
+    match projector.rebuild().await {
+        Ok(projected) => println!("Projected {} auction events", projected),
+        Err(error) => {
+            println!("Failed to rebuild auction read models: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
@@ -4,3 +4,5 @@ pub mod prisma;
 pub mod repository;
 pub mod request;
 pub mod services;
+#[cfg(test)]
+mod test_support;
@@ -1,6 +1,14 @@
 pub mod auction;
 pub mod auction_aggregate;
+pub mod auction_error;
+pub mod auction_history;
 pub mod auction_item;
+pub mod auction_read_model;
 pub mod bid;
+pub mod bid_rejection;
+pub mod bidding_rules;
+pub mod clock;
+pub mod linked_accounts;
 pub mod price;
+pub mod sealed_auction;
 pub mod user;
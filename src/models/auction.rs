@@ -1,180 +1,119 @@
-use crate::models::auction_item::AuctionItem;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 
-use crate::models::price::{Currency, Price};
+use crate::models::price::Price;
 
-use super::{
-    auction_item::AuctionItemId,
-    user::{generate_user, User},
-};
+use super::{auction_aggregate::AuctionId, bid_rejection::BidRejection, user::UserId};
 
-pub struct Auction<'a> {
-    auction_item_id: AuctionItemId,
-    bidding_rules: Vec<Box<dyn Rule>>,
-    bids_received: Vec<Bid<'a>>,
-    opening_bid_price: Price,
+// Rejected bid that looks like shill bidding, kept for moderators to review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationRecord {
+    pub auction_id: AuctionId,
+    pub bidder_id: UserId,
+    pub rejection: BidRejection,
+    pub recorded_at: DateTime<Utc>,
 }
-
-// Auction Aggregate
-// - Enforces the bidding rule invariants
-// - Publishes domain events -> BidPlaced,
-//
-// Creates a notification hook for users who bidded
-// - Publishes domain events to those users
-impl<'a> Auction<'a> {
-    pub fn new(
-        auction_item_id: AuctionItemId,
-        opening_bid_price: Price,
-        minimum_bid_increment: Option<u32>,
-        headshot_bid_amount: Option<u32>,
-    ) -> Self {
-        let same_currency_rule = SameBidCurrency {
-            currency_to_follow: opening_bid_price.currency,
-        };
-        let min_bid_increment_rule = MinimumBidIncrement {
-            // Default min increment of 1
-            min_increment: minimum_bid_increment.unwrap_or(1),
-        };
-        let max_bid_rule = MaximumBid {
-            maximum_bid: headshot_bid_amount,
-        };
-        Self {
-            auction_item_id,
-            bidding_rules: vec![
-                Box::new(same_currency_rule),
-                Box::new(min_bid_increment_rule),
-                Box::new(max_bid_rule),
-            ],
-            bids_received: vec![],
-            opening_bid_price,
+impl ModerationRecord {
+    // None when the rejection is an ordinary one
+    pub fn for_rejection(
+        auction_id: AuctionId,
+        bidder_id: UserId,
+        rejection: &BidRejection,
+        recorded_at: DateTime<Utc>,
+    ) -> Option<Self> {
+        if !rejection.needs_moderation() {
+            return None;
         }
-    }
-
-    pub fn place_bid(&mut self, mut bid: Bid) -> Result<(), String> {
-        // TODO: Shouldn't allow owner of auction item to bid for item?
-        let bid_placed = match self.bids_received.last() {
-            None => {
-                // First bid, should at least match opening bid price
-                if bid.price < self.opening_bid_price {
-                    return Err(
-                        "first bid must be at least as high as the stipulated opening bid".into(),
-                    );
-                }
-                bid.set_increment(bid.price.value);
-                bid
-            }
-            Some(last_bid) => {
-                let increment = bid.price.value - last_bid.price.value;
-                bid.set_increment(increment);
-                bid
-            }
-        };
-        println!("Bid placed : {:#?}", bid_placed);
-        for rule in &self.bidding_rules {
-            rule.enforce(&bid_placed)?
-        }
-        Ok(())
+        Some(Self {
+            auction_id,
+            bidder_id,
+            rejection: rejection.clone(),
+            recorded_at,
+        })
     }
 }
 
-trait Rule {
-    fn enforce(&self, bid: &Bid) -> Result<(), String>;
+// Anti-sniping: a bid accepted within `window` of the deadline pushes it out by `extension`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SoftClose {
+    #[serde(with = "duration_seconds")]
+    pub window: Duration,
+    #[serde(with = "duration_seconds")]
+    pub extension: Duration,
 }
 
-struct SameBidCurrency {
-    currency_to_follow: Currency,
-}
-impl Rule for SameBidCurrency {
-    fn enforce(&self, bid: &Bid) -> Result<(), String> {
-        if bid.price.currency != self.currency_to_follow {
-            return Err("bid's price currency does not match with set currency".into());
-        }
-        Ok(())
-    }
+// Descending price: the asking price drops by `step` every `interval` until a bidder accepts
+// it, the auction ends unsold rather than going below `floor_price`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceDrop {
+    pub step: u32,
+    pub floor_price: u32,
+    #[serde(with = "duration_seconds")]
+    pub interval: Duration,
 }
 
-struct MinimumBidIncrement {
-    min_increment: u32,
-}
-impl Rule for MinimumBidIncrement {
-    fn enforce(&self, bid: &Bid) -> Result<(), String> {
-        if bid.increment.unwrap() < self.min_increment {
-            return Err(
-                "bid's increment must be at least as high as the set minimum bid increment".into(),
-            );
-        }
-        Ok(())
+// Durations are stored as whole seconds
+pub(crate) mod duration_seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_seconds())
     }
-}
 
-struct MaximumBid {
-    maximum_bid: Option<u32>,
-}
-impl Rule for MaximumBid {
-    fn enforce(&self, bid: &Bid) -> Result<(), String> {
-        match self.maximum_bid {
-            None => Ok(()),
-            Some(value) => {
-                if bid.price.value > value {
-                    return Err("bid has exceeded the maximum bid value".into());
-                }
-                Ok(())
-            }
-        }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        i64::deserialize(deserializer).map(Duration::seconds)
     }
 }
 
-#[derive(Debug)]
-pub struct Bid<'a> {
-    by: &'a User,
-    created_at: DateTime<Utc>,
-    increment: Option<u32>,
-    pub price: Price,
+// Created -> Started -> InProgress -> Ending -> Ended, or Closed early by the seller at any point
+// before the auction has ended. Sealed bid auctions go through Revealing between the deadline and
+// Ended.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AuctionStatus {
+    Created,
+    // Open for bids
+    Started,
+    // First bid received
+    InProgress,
+    // Deadline approaching
+    Ending,
+    // Sealed bids are being revealed, no more bids are taken
+    Revealing,
+    // Deadline reached, or won outright
+    Ended,
+    // Closed by the seller prematurely
+    Closed,
 }
-impl<'a> Bid<'a> {
-    pub fn new(by: &'a User, price: Price) -> Self {
-        Self {
-            by,
-            created_at: Utc::now(),
-            increment: None,
-            price,
-        }
+impl AuctionStatus {
+    pub fn is_open_for_bids(&self) -> bool {
+        matches!(self, Self::Started | Self::InProgress | Self::Ending)
     }
 
-    fn set_increment(&mut self, increment: u32) {
-        self.increment = Some(increment);
+    pub fn is_over(&self) -> bool {
+        matches!(self, Self::Ended | Self::Closed)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn minimum_bid_increment_rule() {
-        let user = generate_user();
-        let rule = MinimumBidIncrement { min_increment: 10 };
-        let mut bid = Bid::new(&user, Price::new(Currency::SGD, 10));
-        bid.set_increment(10);
-        assert!(rule.enforce(&bid).is_ok());
-
-        // Should return err if increment is less than min_increment
-        bid.set_increment(8);
-        assert!(rule.enforce(&bid).is_err());
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AuctionOutcome {
+    Sold { price: Price },
+    // No bids, or the highest bid stayed below the reserve price
+    EndedWithoutSale,
+}
+impl AuctionOutcome {
+    pub fn settle(reserve_price: Option<u32>, highest_bid_price: Option<Price>) -> Self {
+        match highest_bid_price {
+            Some(price) if reserve_met(reserve_price, highest_bid_price) => Self::Sold { price },
+            _ => Self::EndedWithoutSale,
+        }
     }
+}
 
-    #[test]
-    fn invalid_bid_less_than_opening_bid() {
-        let user = generate_user();
-        let auction_item = AuctionItem::new(1, String::from("Brass Birmingham"), &user);
-        let mut auction = Auction::new(
-            auction_item.get_id(),
-            Price::new(Currency::SGD, 10),
-            Some(5),
-            None,
-        );
-
-        let invalid_bid = Bid::new(&user, Price::new(Currency::SGD, 8));
-        assert!(auction.place_bid(invalid_bid).is_err());
+pub fn reserve_met(reserve_price: Option<u32>, highest_bid_price: Option<Price>) -> bool {
+    match (reserve_price, highest_bid_price) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(reserve), Some(price)) => price.value >= reserve,
     }
 }
@@ -1,164 +1,1371 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
 use super::{
+    auction::{reserve_met, AuctionOutcome, AuctionStatus, PriceDrop, SoftClose},
+    auction_error::AuctionError,
+    auction_history::PointInTime,
     bid::Bid,
-    price::{Currency, Price},
+    bid_rejection::{BidRejection, BIDDER_ALLOW_LIST, SELLER_CANNOT_BID},
+    bidding_rules::{BiddingRulesConfig, Rule},
+    clock::{Clock, SystemClock},
+    linked_accounts::AccountLinks,
+    price::Price,
+    sealed_auction::{self, BidCommitment, SealedBid, SealedBidTerms, MIN_NONCE_LENGTH},
+    user::UserId,
 };
 
 pub type AuctionId = u32;
 
+// Shape of `AuctionState` as stored in snapshots. Bump whenever fields of the state change, so
+// snapshots taken before are discarded and the state is replayed from events instead.
+pub const SNAPSHOT_VERSION: u32 = 3;
+
+// Auction Aggregate
+// - Enforces the bidding rule invariants
+// - Raises domain events -> BidOffered, ...
+//
+// Raised events are published to in-process subscribers, such as notifications for users who
+// bidded, through the outbox once they are committed
 pub struct AuctionAggregate {
     state: AuctionState,
+    // Version the state was restored from a snapshot at, events up to it are not loaded
+    snapshot_version: EventId,
     domain_events: Vec<AuctionEvent>,
+    // Number of leading domain events that are already in the event store
+    committed_events: usize,
+    clock: Box<dyn Clock>,
+    // Links between accounts, looked up when a bid comes in
+    account_links: Option<Arc<dyn AccountLinks>>,
+    // Context of the command being executed, recorded on the events it raises
+    context: CommandContext,
 }
 impl AuctionAggregate {
     pub fn new(events: Vec<AuctionEvent>) -> Self {
         // TODO: Randomly generate auction id
-        let mut state = AuctionState::new();
-        // Rehydrate state from events
+        Self::rehydrate(AuctionState::new(), 0, events)
+    }
+
+    // Restores the state from the snapshot and replays only the events raised after it
+    pub fn from_snapshot(snapshot: AuctionSnapshot, events: Vec<AuctionEvent>) -> Self {
+        let mut state = snapshot.state;
+        if let Some(bidding_rules) = &state.bidding_rules_config {
+            state.bidding_rules = bidding_rules.build();
+        }
+        Self::rehydrate(state, snapshot.version, events)
+    }
+
+    fn rehydrate(
+        mut state: AuctionState,
+        snapshot_version: EventId,
+        events: Vec<AuctionEvent>,
+    ) -> Self {
         for event in events.iter() {
-            println!(
-                "Applying event id {} of type {:?}..",
-                &event.event_id, &event.event_type
-            );
             state.apply(event);
         }
-        println!("Rehydrated state: {:?}", state);
         Self {
             state,
+            snapshot_version,
+            committed_events: events.len(),
             domain_events: events,
+            clock: Box::new(SystemClock),
+            account_links: None,
+            context: CommandContext::new(),
         }
     }
 
-    pub fn execute(&mut self, cmd: Box<dyn Command>) {
-        match cmd.get_type() {
-            AuctionCommandType::CreateAuction => self.create_auction(),
-            AuctionCommandType::StartAuction => self.start_auction(),
-            AuctionCommandType::CloseAuction => self.close_auction(),
-            AuctionCommandType::MakeBidOffer => self.offer_bid_for_auction(),
+    // Rehydrates the auction only from the events up to the point in time, to look back at its
+    // state. Snapshots are never used as they may be past the point.
+    pub fn as_of(events: Vec<AuctionEvent>, point: PointInTime) -> Self {
+        let version = point.resolve(&events);
+        let events = events
+            .into_iter()
+            .filter(|event| event.get_event_id() <= version)
+            .collect();
+        Self::new(events)
+    }
+
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // Keeps accounts linked to the seller from bidding, on top of the seller themselves
+    pub fn with_account_links(mut self, account_links: Arc<dyn AccountLinks>) -> Self {
+        self.account_links = Some(account_links);
+        self
+    }
+
+    // Runs the command against the current state. On success the events it raised are returned,
+    // a rejected command leaves the aggregate untouched.
+    pub fn execute(&mut self, cmd: AuctionCommand) -> Result<Vec<AuctionEvent>, AuctionError> {
+        self.execute_in_context(cmd, CommandContext::new())
+    }
+
+    // Same as `execute`, with the events raised carrying the actor and ids of the originating
+    // request
+    pub fn execute_in_context(
+        &mut self,
+        cmd: AuctionCommand,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionError> {
+        // Only the events of this command carry its context, whether or not it succeeds
+        let previous_context = std::mem::replace(&mut self.context, context);
+        let result = self.dispatch(cmd);
+        self.context = previous_context;
+        result
+    }
+
+    fn dispatch(&mut self, cmd: AuctionCommand) -> Result<Vec<AuctionEvent>, AuctionError> {
+        let first_new_event = self.domain_events.len();
+        match cmd {
+            AuctionCommand::CreateAuction {
+                auction_id,
+                seller_id,
+                terms,
+            } => self.create_auction(auction_id, seller_id, terms)?,
+            AuctionCommand::StartAuction => self.start_auction()?,
+            AuctionCommand::CloseAuction { closed_by, reason } => {
+                self.close_auction(closed_by, reason)?
+            }
+            AuctionCommand::AnnounceEnding => self.announce_ending()?,
+            AuctionCommand::EndAuction => self.end_auction()?,
+            AuctionCommand::MakeBidOffer { bid } => self.offer_bid_for_auction(bid)?,
+            AuctionCommand::PlaceProxyBid { bid } => self.place_proxy_bid(bid)?,
+            AuctionCommand::AcceptCurrentPrice { bidder_id } => {
+                self.accept_current_price(bidder_id)?
+            }
+            AuctionCommand::DropPrice => self.drop_price()?,
+            AuctionCommand::SubmitSealedBid {
+                bidder_id,
+                commitment,
+            } => self.submit_sealed_bid(bidder_id, commitment)?,
+            AuctionCommand::RevealSealedBid {
+                bidder_id,
+                price,
+                nonce,
+            } => self.reveal_sealed_bid(bidder_id, price, &nonce)?,
         }
+        Ok(self.domain_events[first_new_event..].to_vec())
     }
 
     fn add_domain_event(&mut self, event: AuctionEvent) {
         self.domain_events.push(event);
     }
 
-    fn create_auction(&mut self) {
+    // Applies a newly raised event to the state before keeping it
+    fn raise_event(&mut self, event: AuctionEvent) {
+        self.state.apply(&event);
+        self.add_domain_event(event);
+    }
+
+    fn next_event(&self, event_type: AuctionEventType) -> AuctionEvent {
+        AuctionEvent {
+            metadata: EventMetadata {
+                event_id: Uuid::new_v4(),
+                occurred_at: self.clock.now(),
+                actor_id: self.context.actor_id,
+                correlation_id: self.context.correlation_id,
+                causation_id: self.context.causation_id,
+            },
+            ..AuctionEvent::new(self.get_version() + 1, event_type, self.state.id)
+        }
+    }
+
+    fn create_auction(
+        &mut self,
+        auction_id: AuctionId,
+        seller_id: UserId,
+        terms: AuctionTerms,
+    ) -> Result<(), AuctionError> {
+        if self.get_version() > 0 {
+            return Err(AuctionError::AlreadyCreated {
+                auction_id: self.state.id,
+            });
+        }
+        if terms.asking_price.is_some() && terms.price_drop.is_none() {
+            return Err(AuctionError::InvalidTerms {
+                reason: "asking price needs a price drop",
+            });
+        }
+        if let Some(price_drop) = terms.price_drop {
+            let Some(asking_price) = terms.asking_price else {
+                return Err(AuctionError::NoAskingPrice);
+            };
+            // Sold to whoever accepts the asking price first, nothing else decides the sale
+            if terms.opening_bid_price.is_some()
+                || terms.headshot_bid_amount.is_some()
+                || terms.reserve_price.is_some()
+                || terms.soft_close.is_some()
+            {
+                return Err(AuctionError::InvalidTerms {
+                    reason: "dutch auctions take no opening bid, headshot, reserve or soft close",
+                });
+            }
+            if price_drop.step == 0 {
+                return Err(AuctionError::InvalidTerms {
+                    reason: "price step must be greater than zero",
+                });
+            }
+            if asking_price.value < price_drop.floor_price {
+                return Err(AuctionError::InvalidTerms {
+                    reason: "asking price must not be below the floor price",
+                });
+            }
+        }
+        if terms.sealed_bid.is_some() {
+            if terms.opening_bid_price.is_none() {
+                return Err(AuctionError::InvalidTerms {
+                    reason: "sealed bid auctions need an opening bid price",
+                });
+            }
+            if terms.ends_at.is_none() {
+                return Err(AuctionError::InvalidTerms {
+                    reason: "sealed bid auctions need a deadline",
+                });
+            }
+            if terms.asking_price.is_some() {
+                return Err(AuctionError::InvalidTerms {
+                    reason: "sealed bid auctions have no asking price",
+                });
+            }
+        }
+        let event = AuctionEvent {
+            seller_id: Some(seller_id),
+            opening_bid_price: terms.opening_bid_price,
+            headshot_bid_amount: terms.headshot_bid_amount,
+            reserve_price: terms.reserve_price,
+            price: terms.asking_price,
+            price_drop: terms.price_drop,
+            sealed_bid: terms.sealed_bid,
+            bidding_rules: terms.bidding_rules,
+            ends_at: terms.ends_at,
+            soft_close: terms.soft_close,
+            auction_id,
+            ..self.next_event(AuctionEventType::AuctionCreated)
+        };
+        self.raise_event(event);
+        Ok(())
+    }
+
+    fn current_status(&self) -> Result<AuctionStatus, AuctionError> {
+        self.state.status.ok_or(AuctionError::NotCreated)
+    }
+
+    fn start_auction(&mut self) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        if status != AuctionStatus::Created {
+            return Err(AuctionError::InvalidStatus {
+                status,
+                command: "start",
+            });
+        }
+        let event = self.next_event(AuctionEventType::AuctionStarted);
+        self.raise_event(event);
+        Ok(())
+    }
+
+    fn close_auction(&mut self, closed_by: UserId, reason: String) -> Result<(), AuctionError> {
+        if self.current_status()?.is_over() {
+            return Err(AuctionError::AlreadyEnded);
+        }
+        if self
+            .state
+            .seller_id
+            .is_some_and(|seller_id| seller_id != closed_by)
+        {
+            return Err(AuctionError::NotSeller { user_id: closed_by });
+        }
         let event = AuctionEvent {
-            event_id: 1,
-            event_type: AuctionEventType::AuctionCreated,
-            auction_id: self.state.id,
-            bid: None,
+            reason: Some(reason),
+            ..self.next_event(AuctionEventType::AuctionClosed)
         };
-        self.add_domain_event(event);
+        self.raise_event(event);
+        Ok(())
+    }
+
+    // Deadline is near, bids are still accepted
+    fn announce_ending(&mut self) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        match status {
+            AuctionStatus::Started | AuctionStatus::InProgress => {}
+            status if status.is_over() => return Err(AuctionError::AlreadyEnded),
+            status => {
+                return Err(AuctionError::InvalidStatus {
+                    status,
+                    command: "announce the end of",
+                })
+            }
+        }
+        let event = self.next_event(AuctionEventType::AuctionEnding);
+        self.raise_event(event);
+        Ok(())
+    }
+
+    // Deadline reached, settles the auction against its reserve. Sealed bid auctions first close
+    // bidding and open the reveal window, and are settled once that deadline is reached in turn.
+    fn end_auction(&mut self) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        if status.is_over() {
+            return Err(AuctionError::AlreadyEnded);
+        }
+        if !status.is_open_for_bids() && status != AuctionStatus::Revealing {
+            return Err(AuctionError::InvalidStatus {
+                status,
+                command: "end",
+            });
+        }
+        if let Some(ends_at) = self.state.ends_at {
+            if self.clock.now() < ends_at {
+                return Err(AuctionError::DeadlineNotReached { ends_at });
+            }
+        }
+        let event = match self.state.sealed_bid {
+            Some(terms) if status == AuctionStatus::Revealing => AuctionEvent {
+                bid: self.state.opening_bid_price.and_then(|opening_bid_price| {
+                    sealed_auction::settle(
+                        &self.state.sealed_bids,
+                        opening_bid_price,
+                        terms.settlement,
+                    )
+                }),
+                ..self.next_event(AuctionEventType::AuctionEnded)
+            },
+            Some(terms) => AuctionEvent {
+                ends_at: Some(self.clock.now() + terms.reveal_window),
+                ..self.next_event(AuctionEventType::SealedBiddingClosed)
+            },
+            None => self.next_event(AuctionEventType::AuctionEnded),
+        };
+        self.raise_event(event);
+        Ok(())
+    }
+
+    // Drops the asking price by a step once its interval has passed, ends the auction unsold
+    // instead of going below the floor
+    fn drop_price(&mut self) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        if status.is_over() {
+            return Err(AuctionError::AlreadyEnded);
+        }
+        if !status.is_open_for_bids() {
+            return Err(AuctionError::InvalidStatus {
+                status,
+                command: "drop the price of",
+            });
+        }
+        let (Some(price_drop), Some(asking_price), Some(asking_price_since)) = (
+            self.state.price_drop,
+            self.state.asking_price,
+            self.state.asking_price_since,
+        ) else {
+            return Err(AuctionError::NoPriceDrop);
+        };
+        let due_at = asking_price_since + price_drop.interval;
+        if self.clock.now() < due_at {
+            return Err(AuctionError::PriceDropNotDue { due_at });
+        }
+        let event = match asking_price.value.checked_sub(price_drop.step) {
+            Some(value) if value >= price_drop.floor_price => AuctionEvent {
+                price: Some(Price::new(asking_price.currency, value)),
+                ..self.next_event(AuctionEventType::PriceDropped)
+            },
+            // Nobody accepted the price and there are no bids, so it ends without a sale
+            _ => self.next_event(AuctionEventType::AuctionEnded),
+        };
+        self.raise_event(event);
+        Ok(())
+    }
+
+    // Only the commitment is kept while bidding is open, submitting again replaces the bidder's
+    // earlier sealed bid
+    fn submit_sealed_bid(
+        &mut self,
+        bidder_id: UserId,
+        commitment: BidCommitment,
+    ) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        let Some(opening_bid_price) = self.state.sealed_bid.and(self.state.opening_bid_price)
+        else {
+            return Err(AuctionError::NotSealedBid);
+        };
+        if status.is_over() {
+            return Err(AuctionError::AlreadyEnded);
+        }
+        if !status.is_open_for_bids() {
+            return Err(AuctionError::InvalidStatus {
+                status,
+                command: "submit a sealed bid to",
+            });
+        }
+        if let Some(ends_at) = self.state.ends_at {
+            if self.clock.now() >= ends_at {
+                return Err(BidRejection::DeadlinePassed { ends_at }.into());
+            }
+        }
+        if self.state.sealed_bids.iter().any(|sealed_bid| {
+            sealed_bid.commitment == commitment && sealed_bid.bidder_id != bidder_id
+        }) {
+            return Err(AuctionError::CommitmentTaken);
+        }
+        // Amount is unknown until revealed, so only the rules on who may bid apply
+        self.check_not_linked_to_seller(bidder_id)?;
+        self.state
+            .check_bidder(&Bid::new(bidder_id, opening_bid_price))?;
+        let event = AuctionEvent {
+            bidder_id: Some(bidder_id),
+            commitment: Some(commitment),
+            ..self.next_event(AuctionEventType::SealedBidSubmitted)
+        };
+        self.raise_event(event);
+        Ok(())
+    }
+
+    fn reveal_sealed_bid(
+        &mut self,
+        bidder_id: UserId,
+        price: Price,
+        nonce: &str,
+    ) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        if self.state.sealed_bid.is_none() {
+            return Err(AuctionError::NotSealedBid);
+        }
+        if status.is_over() {
+            return Err(AuctionError::AlreadyEnded);
+        }
+        if status != AuctionStatus::Revealing {
+            return Err(AuctionError::InvalidStatus {
+                status,
+                command: "reveal a sealed bid to",
+            });
+        }
+        if let Some(ends_at) = self.state.ends_at {
+            if self.clock.now() >= ends_at {
+                return Err(AuctionError::RevealWindowClosed { ends_at });
+            }
+        }
+        let Some(sealed_bid) = self
+            .state
+            .sealed_bids
+            .iter()
+            .find(|sealed_bid| sealed_bid.bidder_id == bidder_id)
+        else {
+            return Err(AuctionError::NoSealedBid { bidder_id });
+        };
+        if nonce.chars().count() < MIN_NONCE_LENGTH {
+            return Err(AuctionError::NonceTooShort {
+                minimum_length: MIN_NONCE_LENGTH,
+            });
+        }
+        if sealed_auction::commit(self.state.id, bidder_id, price, nonce) != sealed_bid.commitment {
+            return Err(AuctionError::CommitmentMismatch);
+        }
+        let event = AuctionEvent {
+            bid: Some(Bid::new(bidder_id, price)),
+            ..self.next_event(AuctionEventType::SealedBidRevealed)
+        };
+        self.raise_event(event);
+        Ok(())
+    }
+
+    pub fn get_state(&self) -> &AuctionState {
+        &self.state
+    }
+
+    // Version of the auction's stream, including events not stored yet
+    pub fn get_version(&self) -> EventId {
+        self.snapshot_version + self.domain_events.len() as EventId
+    }
+
+    // Events raised since the aggregate was loaded or last committed
+    pub fn get_uncommitted_events(&self) -> &[AuctionEvent] {
+        &self.domain_events[self.committed_events..]
+    }
+
+    // Version of the stream as it is in the event store
+    pub fn get_committed_version(&self) -> EventId {
+        self.snapshot_version + self.committed_events as EventId
+    }
+
+    pub fn mark_committed(&mut self) {
+        self.committed_events = self.domain_events.len();
+    }
+
+    // State as of the latest version, including events not stored yet
+    pub fn take_snapshot(&self) -> AuctionSnapshot {
+        AuctionSnapshot {
+            snapshot_version: SNAPSHOT_VERSION,
+            version: self.get_version(),
+            state: self.state.clone(),
+        }
+    }
+
+    // Runs the bid through the auction's rule chain, and on success records it along with any
+    // proxy bids and deadline extension it triggers
+    fn offer_bid_for_auction(&mut self, mut bid: Bid) -> Result<(), AuctionError> {
+        if self.state.sealed_bid.is_some() {
+            return Err(AuctionError::SealedBidsOnly);
+        }
+        if self.state.price_drop.is_some() {
+            return Err(AuctionError::DutchAuctionOnly);
+        }
+        // Nobody pays more than the headshot price
+        if let Some(headshot) = self.state.headshot_bid_amount {
+            bid.price.value = bid.price.value.min(headshot);
+        }
+        self.check_not_linked_to_seller(bid.get_bidder_id())?;
+        self.state.validate_bid(&mut bid, self.clock.now())?;
+        println!("Bid placed : {:#?}", bid);
+        self.accept_bid(bid);
+        if !self.state.is_over() {
+            self.resolve_proxy_bids();
+            self.extend_deadline_if_sniped();
+        }
+        Ok(())
+    }
+
+    // Registers a secret maximum for the bidder, carried as the bid's price. The auction bids on
+    // their behalf, one minimum increment at a time, whenever they are outbid until the maximum
+    // is reached. Registering again replaces the bidder's previous maximum.
+    fn place_proxy_bid(&mut self, mut max_bid: Bid) -> Result<(), AuctionError> {
+        if self.state.sealed_bid.is_some() {
+            return Err(AuctionError::SealedBidsOnly);
+        }
+        if self.state.price_drop.is_some() {
+            return Err(AuctionError::DutchAuctionOnly);
+        }
+        if let Some(headshot) = self.state.headshot_bid_amount {
+            max_bid.price.value = max_bid.price.value.min(headshot);
+        }
+        self.check_not_linked_to_seller(max_bid.get_bidder_id())?;
+        // Maximum has to be a valid bid in its own right
+        let mut candidate = max_bid.clone();
+        self.state.validate_bid(&mut candidate, self.clock.now())?;
+
+        let event = AuctionEvent {
+            bid: Some(max_bid),
+            ..self.next_event(AuctionEventType::ProxyBidRegistered)
+        };
+        self.raise_event(event);
+        let bid_count = self.state.bids.len();
+        self.resolve_proxy_bids();
+        if !self.state.is_over() && self.state.bids.len() > bid_count {
+            self.extend_deadline_if_sniped();
+        }
+        Ok(())
+    }
+
+    // Descending price auctions, first bidder to accept the asking price wins
+    fn accept_current_price(&mut self, bidder_id: UserId) -> Result<(), AuctionError> {
+        let status = self.current_status()?;
+        if status.is_over() {
+            return Err(AuctionError::AlreadyEnded);
+        }
+        if !status.is_open_for_bids() {
+            return Err(AuctionError::InvalidStatus {
+                status,
+                command: "accept the current price of",
+            });
+        }
+        let Some(asking_price) = self.state.asking_price else {
+            return Err(AuctionError::NoAskingPrice);
+        };
+        self.check_not_linked_to_seller(bidder_id)?;
+        let bid = Bid::new(bidder_id, asking_price);
+        self.state.check_bidder(&bid)?;
+        let event = AuctionEvent {
+            bid: Some(bid),
+            price: Some(asking_price),
+            ..self.next_event(AuctionEventType::CurrentPriceAccepted)
+        };
+        self.raise_event(event);
+        Ok(())
+    }
+
+    // Seller themselves are kept out by the seller rule
+    fn check_not_linked_to_seller(&self, bidder_id: UserId) -> Result<(), BidRejection> {
+        let (Some(seller_id), Some(account_links)) = (self.state.seller_id, &self.account_links)
+        else {
+            return Ok(());
+        };
+        if bidder_id == seller_id {
+            return Ok(());
+        }
+        match account_links.link_between(seller_id, bidder_id) {
+            Some(link) => Err(BidRejection::LinkedToSeller { link }),
+            None => Ok(()),
+        }
+    }
+
+    fn accept_bid(&mut self, bid: Bid) {
+        let reaches_headshot = self
+            .state
+            .headshot_bid_amount
+            .is_some_and(|headshot| bid.price.value >= headshot);
+        let event_type = if reaches_headshot {
+            AuctionEventType::HeadshotBidPlaced
+        } else {
+            AuctionEventType::BidOffered
+        };
+        let event = AuctionEvent {
+            bid: Some(bid),
+            ..self.next_event(event_type)
+        };
+        self.raise_event(event);
+        // First bid gets the auction going
+        if self.state.status == Some(AuctionStatus::Started) {
+            let event = self.next_event(AuctionEventType::AuctionInProgress);
+            self.raise_event(event);
+        }
+    }
+
+    fn extend_deadline_if_sniped(&mut self) {
+        let (Some(ends_at), Some(soft_close)) = (self.state.ends_at, self.state.soft_close) else {
+            return;
+        };
+        if ends_at - self.clock.now() <= soft_close.window {
+            let ends_at = ends_at + soft_close.extension;
+            let event = AuctionEvent {
+                ends_at: Some(ends_at),
+                ..self.next_event(AuctionEventType::DeadlineExtended)
+            };
+            self.raise_event(event);
+        }
     }
 
-    fn start_auction(&mut self) {}
+    // Settles proxy bids against the standing bid. The highest maximum wins and, between equal
+    // maximums, the one registered first. Only the resulting steps are added to the bid history:
+    // the runner-up's last bid at its maximum followed by the winner's response.
+    fn resolve_proxy_bids(&mut self) {
+        let mut ranked = self.state.proxy_bids.clone();
+        // Stable sort keeps registration order between equal maximums
+        ranked.sort_by(|a, b| b.max_price.value.cmp(&a.max_price.value));
+        let Some(winner) = ranked.first().copied() else {
+            return;
+        };
+        let runner_up = ranked.get(1).copied();
+        let opening_bid_value = self.state.opening_bid_price.map_or(0, |price| price.value);
+        let mut auto_bids = vec![];
+
+        // Winning maximum reaching the headshot price buys the item straight away
+        if Some(winner.max_price.value) == self.state.headshot_bid_amount {
+            let previous = self.state.leading_bid().map_or(0, |last| last.price.value);
+            let mut bid = Bid::new(winner.bidder_id, winner.max_price);
+            bid.set_increment(winner.max_price.value - previous);
+            self.accept_bid(bid);
+            return;
+        }
 
-    fn close_auction(&mut self) {}
+        let mut standing = self
+            .state
+            .leading_bid()
+            .map(|bid| (bid.get_bidder_id(), bid.price.value));
+        if let Some(runner_up) = runner_up {
+            let max = runner_up.max_price.value;
+            let outbids_standing = match standing {
+                None => max >= opening_bid_value,
+                Some((_, price)) => max > price,
+            };
+            if outbids_standing {
+                auto_bids.push(Bid::new(runner_up.bidder_id, runner_up.max_price));
+                standing = Some((runner_up.bidder_id, max));
+            }
+        }
+
+        let winner_price = match standing {
+            None => Some(opening_bid_value),
+            Some((bidder_id, _)) if bidder_id == winner.bidder_id => None,
+            // Equal maximum was registered later, earliest maximum takes the tie
+            Some((_, price)) if price == winner.max_price.value => Some(price),
+            Some((_, price)) if price < winner.max_price.value => {
+                let increment = self.state.minimum_increment_at(price);
+                Some((price + increment).min(winner.max_price.value))
+            }
+            Some(_) => None,
+        };
+        if let Some(value) = winner_price {
+            auto_bids.push(Bid::new(
+                winner.bidder_id,
+                Price::new(winner.max_price.currency, value),
+            ));
+        }
 
-    fn offer_bid_for_auction(&mut self) {}
+        for mut bid in auto_bids {
+            let previous = self.state.leading_bid().map_or(0, |last| last.price.value);
+            bid.set_increment(bid.price.value - previous);
+            self.accept_bid(bid);
+        }
+    }
 }
 
 // All events added to aggregate's events collection are passed to the state projection logic
 // under this class where the relevant field's values are mutated to the events' data
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AuctionState {
     id: AuctionId,
+    // None until the auction is created
+    status: Option<AuctionStatus>,
+    seller_id: Option<UserId>,
     bids: Vec<Bid>,
+    opening_bid_price: Option<Price>,
+    // Buy-it-now price, the first bid reaching it wins the auction outright
+    headshot_bid_amount: Option<u32>,
+    reserve_price: Option<u32>,
+    // Current price of a descending price auction
+    asking_price: Option<Price>,
+    price_drop: Option<PriceDrop>,
+    // When the asking price was set, the next drop is due an interval later
+    asking_price_since: Option<DateTime<Utc>>,
+    sealed_bid: Option<SealedBidTerms>,
+    // Sealed bids in order of submission, with their amounts once revealed
+    sealed_bids: Vec<SealedBid>,
+    // Rule chain rebuilt from the configuration stored with the auction
+    bidding_rules_config: Option<BiddingRulesConfig>,
+    #[serde(skip)]
+    bidding_rules: Vec<Box<dyn Rule>>,
+    // Secret maximums registered by bidders, kept in order of registration. Never part of the
+    // visible bid history
+    proxy_bids: Vec<ProxyBid>,
+    ends_at: Option<DateTime<Utc>>,
+    soft_close: Option<SoftClose>,
+    outcome: Option<AuctionOutcome>,
 }
 impl AuctionState {
     pub fn new() -> Self {
         Self {
             id: 0,
+            status: None,
+            seller_id: None,
             bids: vec![],
+            opening_bid_price: None,
+            headshot_bid_amount: None,
+            reserve_price: None,
+            asking_price: None,
+            price_drop: None,
+            asking_price_since: None,
+            sealed_bid: None,
+            sealed_bids: vec![],
+            bidding_rules_config: None,
+            bidding_rules: vec![],
+            proxy_bids: vec![],
+            ends_at: None,
+            soft_close: None,
+            outcome: None,
+        }
+    }
+
+    pub fn get_id(&self) -> AuctionId {
+        self.id
+    }
+
+    pub fn get_status(&self) -> Option<AuctionStatus> {
+        self.status
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status.is_some_and(|status| status.is_over())
+    }
+
+    pub fn bids(&self) -> &[Bid] {
+        &self.bids
+    }
+
+    pub fn leading_bid(&self) -> Option<&Bid> {
+        self.bids.last()
+    }
+
+    pub fn get_ends_at(&self) -> Option<DateTime<Utc>> {
+        self.ends_at
+    }
+
+    // Reserve amount itself is never exposed to bidders
+    pub fn is_reserve_met(&self) -> bool {
+        reserve_met(self.reserve_price, self.leading_bid().map(|bid| bid.price))
+    }
+
+    pub fn is_won_by_headshot(&self) -> bool {
+        match (self.headshot_bid_amount, self.leading_bid()) {
+            (Some(headshot), Some(bid)) => bid.price.value >= headshot,
+            _ => false,
+        }
+    }
+
+    pub fn get_bidding_rules_config(&self) -> Option<&BiddingRulesConfig> {
+        self.bidding_rules_config.as_ref()
+    }
+
+    // Codes of the bidding rules in force, for clients to know what to check up front
+    pub fn rule_codes(&self) -> Vec<&'static str> {
+        self.bidding_rules.iter().map(|rule| rule.code()).collect()
+    }
+
+    // Lowest bid the auction would currently accept, none once bidding is over
+    pub fn next_minimum_bid(&self) -> Option<Price> {
+        if !self.status.is_some_and(|status| status.is_open_for_bids()) {
+            return None;
+        }
+        match self.leading_bid() {
+            None => self.opening_bid_price,
+            Some(bid) => {
+                let increment = self.minimum_increment_at(bid.price.value);
+                Some(Price::new(bid.price.currency, bid.price.value + increment))
+            }
         }
     }
 
+    pub fn get_outcome(&self) -> Option<AuctionOutcome> {
+        self.outcome
+    }
+
+    fn minimum_increment_at(&self, price: u32) -> u32 {
+        self.bidding_rules_config
+            .as_ref()
+            .map_or(1, |config| config.minimum_increment_at(price))
+    }
+
+    // Rules on who may bid, for ways of buying that leave out the price rules
+    fn check_bidder(&self, bid: &Bid) -> Result<(), BidRejection> {
+        self.bidding_rules
+            .iter()
+            .filter(|rule| matches!(rule.code(), SELLER_CANNOT_BID | BIDDER_ALLOW_LIST))
+            .try_for_each(|rule| rule.enforce(bid))
+    }
+
+    fn validate_bid(&self, bid: &mut Bid, now: DateTime<Utc>) -> Result<(), BidRejection> {
+        if self.is_won_by_headshot() {
+            return Err(BidRejection::AuctionWonByHeadshot {
+                price: self.leading_bid().unwrap().price,
+            });
+        }
+        match self.status {
+            Some(status) if status.is_open_for_bids() => {}
+            Some(status) if status.is_over() => return Err(BidRejection::AuctionClosed),
+            _ => return Err(BidRejection::AuctionNotStarted),
+        }
+        if let Some(ends_at) = self.ends_at {
+            if now >= ends_at {
+                return Err(BidRejection::DeadlinePassed { ends_at });
+            }
+        }
+        match self.leading_bid() {
+            None => {
+                // First bid, should at least match opening bid price
+                if let Some(opening_bid_price) = self.opening_bid_price {
+                    if bid.price < opening_bid_price {
+                        return Err(BidRejection::BelowOpeningPrice { opening_bid_price });
+                    }
+                }
+                bid.set_increment(bid.price.value);
+            }
+            Some(last_bid) => {
+                // Rules only see the increment, which cannot tell how far below the leading
+                // bid this one is
+                if bid.price.currency == last_bid.price.currency
+                    && bid.price.value <= last_bid.price.value
+                {
+                    if let Some(next_minimum_bid) = self.next_minimum_bid() {
+                        return Err(BidRejection::IncrementTooSmall {
+                            minimum_increment: self.minimum_increment_at(last_bid.price.value),
+                            next_minimum_bid,
+                        });
+                    }
+                }
+                let increment = bid.price.value.saturating_sub(last_bid.price.value);
+                bid.set_increment(increment);
+            }
+        };
+        for rule in &self.bidding_rules {
+            rule.enforce(bid)?
+        }
+        Ok(())
+    }
+
     pub fn apply(&mut self, event: &AuctionEvent) {
         match event.event_type {
-            AuctionEventType::AuctionCreated => self.id = event.auction_id,
-            AuctionEventType::AuctionStarted => println!("Auction started"),
-            AuctionEventType::AuctionClosed => println!("Auction closed!"),
+            AuctionEventType::AuctionCreated => {
+                self.id = event.auction_id;
+                self.status = Some(AuctionStatus::Created);
+                self.seller_id = event.seller_id;
+                self.opening_bid_price = event.opening_bid_price;
+                self.headshot_bid_amount = event.headshot_bid_amount;
+                self.reserve_price = event.reserve_price;
+                self.asking_price = event.price;
+                self.price_drop = event.price_drop;
+                self.sealed_bid = event.sealed_bid;
+                self.ends_at = event.ends_at;
+                self.soft_close = event.soft_close;
+                // Auctions with an opening price and no configuration of their own get the
+                // default rules, with a minimum increment of 1
+                let mut bidding_rules_config = event.bidding_rules.clone().or_else(|| {
+                    self.opening_bid_price
+                        .map(|price| BiddingRulesConfig::default_for(price.currency, 1))
+                });
+                // Price comparisons ignore currency, so bids always stay in the auction's
+                // currency whether or not the configuration says so
+                if let Some(price) = self.opening_bid_price.or(self.asking_price) {
+                    bidding_rules_config = Some(
+                        bidding_rules_config
+                            .unwrap_or_default()
+                            .with_bid_currency(price.currency),
+                    );
+                }
+                // No auction lets its seller bid
+                self.bidding_rules_config = match self.seller_id {
+                    Some(seller_id) => Some(
+                        bidding_rules_config
+                            .unwrap_or_default()
+                            .with_seller_cannot_bid(seller_id),
+                    ),
+                    None => bidding_rules_config,
+                };
+                if let Some(bidding_rules) = &self.bidding_rules_config {
+                    self.bidding_rules = bidding_rules.build();
+                }
+            }
+            AuctionEventType::PriceDropped => {
+                self.asking_price = event.price;
+                self.asking_price_since = Some(event.metadata.occurred_at);
+            }
+            AuctionEventType::CurrentPriceAccepted => match &event.bid {
+                Some(bid) => {
+                    self.bids.push(bid.clone());
+                    self.status = Some(AuctionStatus::Ended);
+                    self.outcome = Some(AuctionOutcome::Sold { price: bid.price });
+                }
+                None => println!("No bid found for event"),
+            },
+            AuctionEventType::AuctionStarted => {
+                self.status = Some(AuctionStatus::Started);
+                self.asking_price_since = Some(event.metadata.occurred_at);
+            }
+            AuctionEventType::AuctionInProgress => self.status = Some(AuctionStatus::InProgress),
+            AuctionEventType::AuctionEnding => self.status = Some(AuctionStatus::Ending),
+            AuctionEventType::AuctionEnded => {
+                // Winning bid of a sealed bid auction, at the price paid
+                if let Some(bid) = &event.bid {
+                    self.bids.push(bid.clone());
+                }
+                self.status = Some(AuctionStatus::Ended);
+                self.settle();
+            }
+            AuctionEventType::AuctionClosed => {
+                self.status = Some(AuctionStatus::Closed);
+                self.settle();
+            }
             AuctionEventType::BidOffered => match &event.bid {
                 Some(bid) => self.bids.push(bid.clone()),
                 None => println!("No bid found for event"),
             },
-            _ => println!("Received unknown event"),
+            AuctionEventType::ProxyBidRegistered => match &event.bid {
+                Some(bid) => {
+                    let bidder_id = bid.get_bidder_id();
+                    self.proxy_bids
+                        .retain(|proxy_bid| proxy_bid.bidder_id != bidder_id);
+                    self.proxy_bids.push(ProxyBid {
+                        bidder_id,
+                        max_price: bid.price,
+                    });
+                }
+                None => println!("No bid found for event"),
+            },
+            AuctionEventType::HeadshotBidPlaced => match &event.bid {
+                Some(bid) => {
+                    self.bids.push(bid.clone());
+                    self.status = Some(AuctionStatus::Ended);
+                    self.outcome = Some(AuctionOutcome::Sold { price: bid.price });
+                }
+                None => println!("No bid found for event"),
+            },
+            AuctionEventType::DeadlineExtended => self.ends_at = event.ends_at,
+            AuctionEventType::SealedBidSubmitted => match (event.bidder_id, event.commitment) {
+                (Some(bidder_id), Some(commitment)) => {
+                    self.sealed_bids
+                        .retain(|sealed_bid| sealed_bid.bidder_id != bidder_id);
+                    self.sealed_bids.push(SealedBid {
+                        bidder_id,
+                        commitment,
+                        revealed_price: None,
+                    });
+                }
+                _ => println!("No sealed bid found for event"),
+            },
+            AuctionEventType::SealedBiddingClosed => {
+                self.status = Some(AuctionStatus::Revealing);
+                self.ends_at = event.ends_at;
+            }
+            AuctionEventType::SealedBidRevealed => match &event.bid {
+                Some(bid) => {
+                    if let Some(sealed_bid) = self
+                        .sealed_bids
+                        .iter_mut()
+                        .find(|sealed_bid| sealed_bid.bidder_id == bid.get_bidder_id())
+                    {
+                        sealed_bid.revealed_price = Some(bid.price);
+                    }
+                }
+                None => println!("No bid found for event"),
+            },
         }
     }
+
+    fn settle(&mut self) {
+        self.outcome = Some(AuctionOutcome::settle(
+            self.reserve_price,
+            self.bids.last().map(|bid| bid.price),
+        ));
+    }
 }
 
-// NOTE: Does it make sense to generalize with trait like that?
-pub trait Command {
-    fn get_type(&self) -> AuctionCommandType;
+impl Clone for AuctionState {
+    // Rules are not cloneable, the copy rebuilds its own from the configuration
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            status: self.status,
+            seller_id: self.seller_id,
+            bids: self.bids.clone(),
+            opening_bid_price: self.opening_bid_price,
+            headshot_bid_amount: self.headshot_bid_amount,
+            reserve_price: self.reserve_price,
+            asking_price: self.asking_price,
+            price_drop: self.price_drop,
+            asking_price_since: self.asking_price_since,
+            sealed_bid: self.sealed_bid,
+            sealed_bids: self.sealed_bids.clone(),
+            bidding_rules_config: self.bidding_rules_config.clone(),
+            bidding_rules: self
+                .bidding_rules_config
+                .as_ref()
+                .map_or(vec![], |config| config.build()),
+            proxy_bids: self.proxy_bids.clone(),
+            ends_at: self.ends_at,
+            soft_close: self.soft_close,
+            outcome: self.outcome,
+        }
+    }
 }
 
-pub struct CreateAuctionCommand {
-    command_type: AuctionCommandType,
+// Auction state as of a version of its stream, so loading does not replay every event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionSnapshot {
+    snapshot_version: u32,
+    version: EventId,
+    state: AuctionState,
 }
-impl Command for CreateAuctionCommand {
-    fn get_type(&self) -> AuctionCommandType {
-        self.command_type.clone()
+impl AuctionSnapshot {
+    pub fn get_snapshot_version(&self) -> u32 {
+        self.snapshot_version
+    }
+
+    pub fn get_version(&self) -> EventId {
+        self.version
+    }
+
+    // Taken with the current shape of the state, older ones are discarded
+    pub fn is_current(&self) -> bool {
+        self.snapshot_version == SNAPSHOT_VERSION
     }
 }
 
-pub struct StartAuctionCommand {
-    command_type: AuctionCommandType,
+// Hidden maximum a bidder is willing to pay
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct ProxyBid {
+    bidder_id: UserId,
+    max_price: Price,
+}
+
+// COMMANDS
+#[derive(Debug, Clone)]
+pub enum AuctionCommand {
+    CreateAuction {
+        auction_id: AuctionId,
+        seller_id: UserId,
+        terms: AuctionTerms,
+    },
+    StartAuction,
+    // Seller ending the auction ahead of its deadline
+    CloseAuction {
+        closed_by: UserId,
+        reason: String,
+    },
+    // Raised by the deadline scheduler ahead of the deadline
+    AnnounceEnding,
+    // Raised by the deadline scheduler once the deadline is reached
+    EndAuction,
+    MakeBidOffer {
+        bid: Bid,
+    },
+    // Bid price is the bidder's secret maximum
+    PlaceProxyBid {
+        bid: Bid,
+    },
+    // Descending price auctions
+    AcceptCurrentPrice {
+        bidder_id: UserId,
+    },
+    // Raised by the dutch auction ticker on the auction's price drop interval
+    DropPrice,
+    // Sealed bid auctions, the commitment is made with `sealed_auction::commit`
+    SubmitSealedBid {
+        bidder_id: UserId,
+        commitment: BidCommitment,
+    },
+    // Price and nonce behind the bidder's commitment, once bidding has closed
+    RevealSealedBid {
+        bidder_id: UserId,
+        price: Price,
+        nonce: String,
+    },
 }
-impl Command for StartAuctionCommand {
-    fn get_type(&self) -> AuctionCommandType {
-        self.command_type.clone()
+impl AuctionCommand {
+    // Bidder behind a command that bids on the auction
+    pub fn get_bidder_id(&self) -> Option<UserId> {
+        match self {
+            Self::MakeBidOffer { bid } | Self::PlaceProxyBid { bid } => Some(bid.get_bidder_id()),
+            Self::AcceptCurrentPrice { bidder_id }
+            | Self::SubmitSealedBid { bidder_id, .. }
+            | Self::RevealSealedBid { bidder_id, .. } => Some(*bidder_id),
+            _ => None,
+        }
     }
 }
 
-pub struct CloseAuctionCommand {
-    command_type: AuctionCommandType,
+// Where a command came from. The correlation id is carried over from the originating websocket or
+// HTTP request, the causation id is the id of the request or event that triggered the command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommandContext {
+    actor_id: Option<UserId>,
+    correlation_id: Uuid,
+    causation_id: Option<Uuid>,
 }
-impl Command for CloseAuctionCommand {
-    fn get_type(&self) -> AuctionCommandType {
-        self.command_type.clone()
+impl CommandContext {
+    // Starts a new correlation, for commands nothing else led to
+    pub fn new() -> Self {
+        Self {
+            actor_id: None,
+            correlation_id: Uuid::new_v4(),
+            causation_id: None,
+        }
+    }
+
+    pub fn with_actor(mut self, actor_id: UserId) -> Self {
+        self.actor_id = Some(actor_id);
+        self
     }
-}
 
-pub struct MakeBidOfferCommand {
-    command_type: AuctionCommandType,
+    pub fn with_correlation_id(mut self, correlation_id: Uuid) -> Self {
+        self.correlation_id = correlation_id;
+        self
+    }
+
+    pub fn with_causation_id(mut self, causation_id: Uuid) -> Self {
+        self.causation_id = Some(causation_id);
+        self
+    }
+
+    pub fn get_actor_id(&self) -> Option<UserId> {
+        self.actor_id
+    }
+
+    pub fn get_correlation_id(&self) -> Uuid {
+        self.correlation_id
+    }
+
+    pub fn get_causation_id(&self) -> Option<Uuid> {
+        self.causation_id
+    }
 }
-impl Command for MakeBidOfferCommand {
-    fn get_type(&self) -> AuctionCommandType {
-        self.command_type.clone()
+impl Default for CommandContext {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-// COMMANDS
-#[derive(Clone)]
-pub enum AuctionCommandType {
-    CreateAuction,
-    StartAuction,
-    CloseAuction,
-    MakeBidOffer,
+// Seller's terms for a new auction. English auctions set an opening bid price, descending price
+// auctions an asking price and how it drops, sealed bid auctions an opening bid price, a deadline
+// and how bids are revealed.
+#[derive(Debug, Clone, Default)]
+pub struct AuctionTerms {
+    pub opening_bid_price: Option<Price>,
+    pub asking_price: Option<Price>,
+    pub price_drop: Option<PriceDrop>,
+    pub sealed_bid: Option<SealedBidTerms>,
+    pub headshot_bid_amount: Option<u32>,
+    pub reserve_price: Option<u32>,
+    pub bidding_rules: Option<BiddingRulesConfig>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub soft_close: Option<SoftClose>,
 }
 
 // DOMAIN EVENTS
 
 // BidReceived
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AuctionEvent {
-    // Event information
+    // Event information, `event_id` is the version of the auction's stream the event is at
     event_id: EventId,
     event_type: AuctionEventType,
+    metadata: EventMetadata,
 
     // Auction information
     auction_id: AuctionId,
 
     // Bid information for auction
     bid: Option<Bid>,
+
+    // Set on creation
+    seller_id: Option<UserId>,
+    opening_bid_price: Option<Price>,
+    headshot_bid_amount: Option<u32>,
+
+    // Set by the seller on creation, hidden from bidders
+    reserve_price: Option<u32>,
+
+    // Asking price of a descending price auction, on creation and on every price drop
+    price: Option<Price>,
+    price_drop: Option<PriceDrop>,
+
+    // Sealed bid auctions, terms set on creation and the commitment of each sealed bid
+    sealed_bid: Option<SealedBidTerms>,
+    bidder_id: Option<UserId>,
+    commitment: Option<BidCommitment>,
+
+    // Organizer's rule configuration, set on creation
+    bidding_rules: Option<BiddingRulesConfig>,
+
+    // Deadline set on creation, and pushed out by late bids
+    ends_at: Option<DateTime<Utc>>,
+    soft_close: Option<SoftClose>,
+
+    // Given by the seller when closing the auction early
+    reason: Option<String>,
+}
+impl AuctionEvent {
+    fn new(event_id: EventId, event_type: AuctionEventType, auction_id: AuctionId) -> Self {
+        Self {
+            event_id,
+            event_type,
+            metadata: EventMetadata::default(),
+            auction_id,
+            bid: None,
+            seller_id: None,
+            opening_bid_price: None,
+            headshot_bid_amount: None,
+            reserve_price: None,
+            price: None,
+            price_drop: None,
+            sealed_bid: None,
+            bidder_id: None,
+            commitment: None,
+            bidding_rules: None,
+            ends_at: None,
+            soft_close: None,
+            reason: None,
+        }
+    }
+
+    pub fn get_event_id(&self) -> EventId {
+        self.event_id
+    }
+
+    pub fn get_event_type(&self) -> AuctionEventType {
+        self.event_type
+    }
+
+    pub fn get_metadata(&self) -> &EventMetadata {
+        &self.metadata
+    }
+
+    pub fn get_auction_id(&self) -> AuctionId {
+        self.auction_id
+    }
+
+    pub fn get_bid(&self) -> Option<&Bid> {
+        self.bid.as_ref()
+    }
+
+    pub fn get_seller_id(&self) -> Option<UserId> {
+        self.seller_id
+    }
+
+    pub fn get_opening_bid_price(&self) -> Option<Price> {
+        self.opening_bid_price
+    }
+
+    // Asking price of a descending price auction
+    pub fn get_price(&self) -> Option<Price> {
+        self.price
+    }
+
+    pub fn get_ends_at(&self) -> Option<DateTime<Utc>> {
+        self.ends_at
+    }
+
+    // Event as it may be seen outside the auction. The maximum of a proxy bid, sealed bids until
+    // settlement, the reserve price and the bidding rules, with who may bid and who is kept from
+    // bidding, stay secret. None when nothing is left to show.
+    pub fn to_public(&self) -> Option<Self> {
+        match self.event_type {
+            AuctionEventType::ProxyBidRegistered | AuctionEventType::SealedBidRevealed => None,
+            _ => Some(Self {
+                reserve_price: None,
+                bidding_rules: None,
+                commitment: None,
+                ..self.clone()
+            }),
+        }
+    }
+}
+
+pub type EventId = u32;
+
+// Envelope of an event, for auditing and tracing it back to the request it came from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventMetadata {
+    // Unique across all streams
+    event_id: Uuid,
+    occurred_at: DateTime<Utc>,
+    // User whose command raised the event, none for system commands
+    actor_id: Option<UserId>,
+    correlation_id: Uuid,
+    causation_id: Option<Uuid>,
+}
+impl EventMetadata {
+    pub fn get_event_id(&self) -> Uuid {
+        self.event_id
+    }
+
+    pub fn get_occurred_at(&self) -> DateTime<Utc> {
+        self.occurred_at
+    }
+
+    pub fn get_actor_id(&self) -> Option<UserId> {
+        self.actor_id
+    }
+
+    pub fn get_correlation_id(&self) -> Uuid {
+        self.correlation_id
+    }
+
+    pub fn get_causation_id(&self) -> Option<Uuid> {
+        self.causation_id
+    }
 }
 
-type EventId = u32;
+// Shape of `AuctionEvent` payloads as written by this build. Bump whenever the event changes,
+// along with an upcaster that brings payloads of the previous version to the new shape.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
 
-#[derive(Debug)]
-enum AuctionEventType {
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AuctionEventType {
     AuctionCreated,
     AuctionStarted,
+    // First bid received
     AuctionInProgress,
     // Auction's deadline approaching
     AuctionEnding,
@@ -167,32 +1374,97 @@ enum AuctionEventType {
     // Auction is closed by owner prematurely
     AuctionClosed,
     BidOffered,
-    HeadshotBidPlaced, // TODO: Maybe don't need this as can infer from BidPlaced
+    // Bidder's secret maximum, never part of the visible bid history
+    ProxyBidRegistered,
+    // Bid reaching the headshot price, wins the auction outright
+    HeadshotBidPlaced,
+    // Late bid pushed the deadline out
+    DeadlineExtended,
+    // Descending price auctions
+    PriceDropped,
+    CurrentPriceAccepted,
+    // Sealed bid auctions, the deadline closes bidding and opens the reveal window. The auction
+    // ends once the window is over, with the winning bid if any.
+    SealedBidSubmitted,
+    SealedBiddingClosed,
+    SealedBidRevealed,
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::Duration;
+
+    use crate::{
+        models::{
+            auction::{ModerationRecord, PriceDrop},
+            auction_item::AuctionItem,
+            bidding_rules::{IncrementTier, RuleSpec},
+            clock::ManualClock,
+            linked_accounts::{AccountLink, LinkedAccountRegistry, RegisteredAccounts},
+            price::Currency,
+            user::{generate_user, generate_user_with_contact},
+        },
+        test_support::sgd,
+    };
+
     use super::*;
 
+    // English auction opening at 10 SGD with a minimum increment of 5
+    fn english_auction_terms() -> AuctionTerms {
+        AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            bidding_rules: Some(BiddingRulesConfig::default_for(Currency::SGD, 5)),
+            ..AuctionTerms::default()
+        }
+    }
+
+    fn create_auction(seller_id: UserId, terms: AuctionTerms) -> AuctionAggregate {
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        auction_aggregate
+            .execute(AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id,
+                terms,
+            })
+            .unwrap();
+        auction_aggregate
+    }
+
+    fn start_auction(seller_id: UserId, terms: AuctionTerms) -> AuctionAggregate {
+        let mut auction_aggregate = create_auction(seller_id, terms);
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        auction_aggregate
+    }
+
+    fn offer_bid(
+        auction_aggregate: &mut AuctionAggregate,
+        bidder_id: UserId,
+        price: Price,
+    ) -> Result<Vec<AuctionEvent>, AuctionError> {
+        auction_aggregate.execute(AuctionCommand::MakeBidOffer {
+            bid: Bid::new(bidder_id, price),
+        })
+    }
+
+    fn place_proxy_bid(
+        auction_aggregate: &mut AuctionAggregate,
+        bidder_id: UserId,
+        max_price: Price,
+    ) -> Result<Vec<AuctionEvent>, AuctionError> {
+        auction_aggregate.execute(AuctionCommand::PlaceProxyBid {
+            bid: Bid::new(bidder_id, max_price),
+        })
+    }
+
     #[test]
     fn init_auction_aggregate_from_events() {
-        let auction_created_event = AuctionEvent {
-            event_id: 1,
-            event_type: AuctionEventType::AuctionCreated,
-            auction_id: 1,
-            bid: None,
-        };
-        let auction_started_event = AuctionEvent {
-            event_id: 2,
-            event_type: AuctionEventType::AuctionStarted,
-            auction_id: 1,
-            bid: None,
-        };
+        let auction_created_event = AuctionEvent::new(1, AuctionEventType::AuctionCreated, 1);
+        let auction_started_event = AuctionEvent::new(2, AuctionEventType::AuctionStarted, 1);
         let bid_offered_event = AuctionEvent {
-            event_id: 3,
-            event_type: AuctionEventType::BidOffered,
-            auction_id: 1,
-            bid: Some(Bid::new(Price::new(Currency::MYR, 100))),
+            bid: Some(Bid::new(1, Price::new(Currency::MYR, 100))),
+            ..AuctionEvent::new(3, AuctionEventType::BidOffered, 1)
         };
 
         let domain_events = vec![
@@ -203,4 +1475,834 @@ mod tests {
 
         let _auction_aggregate = AuctionAggregate::new(domain_events);
     }
+
+    #[test]
+    fn closing_below_reserve_ends_without_sale() {
+        let seller = generate_user();
+        let terms = AuctionTerms {
+            reserve_price: Some(150),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(seller.get_id(), terms);
+        offer_bid(&mut auction_aggregate, 2, sgd(100)).unwrap();
+        assert!(!auction_aggregate.get_state().is_reserve_met());
+
+        let events = auction_aggregate
+            .execute(AuctionCommand::CloseAuction {
+                closed_by: seller.get_id(),
+                reason: String::from("Item damaged"),
+            })
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].get_event_type(), AuctionEventType::AuctionClosed);
+        assert_eq!(events[0].reason.as_deref(), Some("Item damaged"));
+        assert_eq!(
+            auction_aggregate.get_state().get_outcome(),
+            Some(AuctionOutcome::EndedWithoutSale)
+        );
+    }
+
+    #[test]
+    fn only_seller_closes_auction() {
+        let seller = generate_user();
+        let bidder = generate_user();
+        let mut auction_aggregate = start_auction(seller.get_id(), english_auction_terms());
+
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::CloseAuction {
+                closed_by: bidder.get_id(),
+                reason: String::from("Changed my mind"),
+            }),
+            Err(AuctionError::NotSeller {
+                user_id: bidder.get_id()
+            })
+        );
+        assert_eq!(auction_aggregate.get_state().get_outcome(), None);
+    }
+
+    #[test]
+    fn creating_auction_twice_is_rejected() {
+        let mut auction_aggregate = create_auction(1, english_auction_terms());
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::CreateAuction {
+                auction_id: 2,
+                seller_id: 1,
+                terms: english_auction_terms(),
+            }),
+            Err(AuctionError::AlreadyCreated { auction_id: 1 })
+        );
+    }
+
+    #[test]
+    fn auction_goes_through_lifecycle_up_to_deadline() {
+        let user = generate_user();
+        let clock = ManualClock::new(Utc::now());
+        let ends_at = clock.now() + Duration::minutes(30);
+        let terms = AuctionTerms {
+            ends_at: Some(ends_at),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = create_auction(1, terms).with_clock(Box::new(clock.clone()));
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Created)
+        );
+
+        // Not open for bids before it is started
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, user.get_id(), sgd(10)),
+            Err(AuctionError::BidRejected(BidRejection::AuctionNotStarted))
+        );
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::AnnounceEnding),
+            Err(AuctionError::InvalidStatus {
+                status: AuctionStatus::Created,
+                command: "announce the end of",
+            })
+        );
+
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(10)).unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::InProgress)
+        );
+
+        clock.advance(Duration::minutes(25));
+        auction_aggregate
+            .execute(AuctionCommand::AnnounceEnding)
+            .unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Ending)
+        );
+        // Still taking bids while ending
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(15)).unwrap();
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::EndAuction),
+            Err(AuctionError::DeadlineNotReached { ends_at })
+        );
+
+        clock.advance(Duration::minutes(5));
+        auction_aggregate
+            .execute(AuctionCommand::EndAuction)
+            .unwrap();
+        let state = auction_aggregate.get_state();
+        assert_eq!(state.get_status(), Some(AuctionStatus::Ended));
+        assert_eq!(
+            state.get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(15) })
+        );
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::CloseAuction {
+                closed_by: 1,
+                reason: String::from("Too late"),
+            }),
+            Err(AuctionError::AlreadyEnded)
+        );
+    }
+
+    #[test]
+    fn commands_on_missing_auction_are_rejected() {
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::StartAuction),
+            Err(AuctionError::NotCreated)
+        );
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, 1, sgd(10)),
+            Err(AuctionError::BidRejected(BidRejection::AuctionNotStarted))
+        );
+    }
+
+    #[test]
+    fn starting_auction_twice_is_rejected() {
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::StartAuction),
+            Err(AuctionError::InvalidStatus {
+                status: AuctionStatus::Started,
+                command: "start",
+            })
+        );
+    }
+
+    fn dutch_auction_terms(step: u32, floor_price: u32) -> AuctionTerms {
+        AuctionTerms {
+            asking_price: Some(sgd(100)),
+            price_drop: Some(PriceDrop {
+                step,
+                floor_price,
+                interval: Duration::minutes(1),
+            }),
+            ..AuctionTerms::default()
+        }
+    }
+
+    #[test]
+    fn accepting_dropped_price_sells_dutch_auction() {
+        let clock = ManualClock::new(Utc::now());
+        let mut auction_aggregate =
+            create_auction(1, dutch_auction_terms(10, 40)).with_clock(Box::new(clock.clone()));
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::DropPrice),
+            Err(AuctionError::PriceDropNotDue {
+                due_at: clock.now() + Duration::minutes(1)
+            })
+        );
+
+        clock.advance(Duration::minutes(1));
+        auction_aggregate
+            .execute(AuctionCommand::DropPrice)
+            .unwrap();
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::AcceptCurrentPrice { bidder_id: 1 }),
+            Err(AuctionError::BidRejected(BidRejection::SellerCannotBid))
+        );
+        auction_aggregate
+            .execute(AuctionCommand::AcceptCurrentPrice { bidder_id: 2 })
+            .unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(90) })
+        );
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::AcceptCurrentPrice { bidder_id: 3 }),
+            Err(AuctionError::AlreadyEnded)
+        );
+        // No more price drops once sold
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::DropPrice),
+            Err(AuctionError::AlreadyEnded)
+        );
+    }
+
+    #[test]
+    fn dutch_auction_ends_unsold_instead_of_dropping_below_floor() {
+        let clock = ManualClock::new(Utc::now());
+        let mut auction_aggregate =
+            create_auction(1, dutch_auction_terms(30, 40)).with_clock(Box::new(clock.clone()));
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+
+        let mut event_types = vec![];
+        for _ in 0..3 {
+            clock.advance(Duration::minutes(1));
+            let events = auction_aggregate
+                .execute(AuctionCommand::DropPrice)
+                .unwrap();
+            event_types.push(events[0].get_event_type());
+        }
+        assert_eq!(
+            event_types,
+            vec![
+                AuctionEventType::PriceDropped,
+                AuctionEventType::PriceDropped,
+                AuctionEventType::AuctionEnded
+            ]
+        );
+        assert_eq!(
+            auction_aggregate.get_state().get_outcome(),
+            Some(AuctionOutcome::EndedWithoutSale)
+        );
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::AcceptCurrentPrice { bidder_id: 2 }),
+            Err(AuctionError::AlreadyEnded)
+        );
+
+        let invalid_terms = [
+            (
+                dutch_auction_terms(10, 120),
+                "asking price must not be below the floor price",
+            ),
+            (
+                AuctionTerms {
+                    price_drop: None,
+                    ..dutch_auction_terms(10, 40)
+                },
+                "asking price needs a price drop",
+            ),
+            (
+                AuctionTerms {
+                    reserve_price: Some(60),
+                    ..dutch_auction_terms(10, 40)
+                },
+                "dutch auctions take no opening bid, headshot, reserve or soft close",
+            ),
+            (
+                AuctionTerms {
+                    asking_price: Some(sgd(100)),
+                    ..english_auction_terms()
+                },
+                "asking price needs a price drop",
+            ),
+        ];
+        for (terms, reason) in invalid_terms {
+            let mut auction_aggregate = AuctionAggregate::new(vec![]);
+            assert_eq!(
+                auction_aggregate.execute(AuctionCommand::CreateAuction {
+                    auction_id: 2,
+                    seller_id: 1,
+                    terms,
+                }),
+                Err(AuctionError::InvalidTerms { reason })
+            );
+        }
+    }
+
+    #[test]
+    fn open_bids_on_dutch_auction_are_rejected() {
+        let clock = ManualClock::new(Utc::now());
+        let mut auction_aggregate =
+            create_auction(1, dutch_auction_terms(30, 40)).with_clock(Box::new(clock.clone()));
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::MakeBidOffer {
+                bid: Bid::new(2, sgd(20)),
+            }),
+            Err(AuctionError::DutchAuctionOnly)
+        );
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::PlaceProxyBid {
+                bid: Bid::new(3, sgd(200)),
+            }),
+            Err(AuctionError::DutchAuctionOnly)
+        );
+
+        // Reaching the floor ends it unsold, with no stray bid to sell to
+        for _ in 0..3 {
+            clock.advance(Duration::minutes(1));
+            auction_aggregate
+                .execute(AuctionCommand::DropPrice)
+                .unwrap();
+        }
+        let state = auction_aggregate.get_state();
+        assert!(state.bids().is_empty());
+        assert_eq!(state.get_outcome(), Some(AuctionOutcome::EndedWithoutSale));
+    }
+
+    #[test]
+    fn rehydration_rebuilds_configured_rule_chain() {
+        let bidding_rules = BiddingRulesConfig::default_for(Currency::SGD, 5);
+        let auction_created_event = AuctionEvent {
+            bidding_rules: Some(bidding_rules),
+            ..AuctionEvent::new(1, AuctionEventType::AuctionCreated, 1)
+        };
+
+        let auction_aggregate = AuctionAggregate::new(vec![auction_created_event]);
+        assert_eq!(
+            auction_aggregate.get_state().rule_codes(),
+            vec!["same_bid_currency", "minimum_bid_increment"]
+        );
+    }
+
+    #[test]
+    fn events_round_trip_through_json() {
+        let terms = AuctionTerms {
+            ends_at: Some(Utc::now()),
+            soft_close: Some(SoftClose {
+                window: Duration::minutes(5),
+                extension: Duration::minutes(2),
+            }),
+            ..english_auction_terms()
+        };
+        let auction_aggregate = start_auction(1, terms);
+
+        for event in auction_aggregate.get_uncommitted_events() {
+            let json = serde_json::to_string(event).expect("Failed to serialize");
+            let deserialized: AuctionEvent =
+                serde_json::from_str(&json).expect("Failed to deserialize");
+            assert_eq!(&deserialized, event);
+        }
+    }
+
+    #[test]
+    fn new_events_stay_uncommitted_until_marked() {
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        auction_aggregate
+            .execute(AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms: english_auction_terms(),
+            })
+            .unwrap();
+        assert_eq!(auction_aggregate.get_committed_version(), 0);
+        assert_eq!(auction_aggregate.get_version(), 1);
+
+        auction_aggregate.mark_committed();
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        assert_eq!(auction_aggregate.get_committed_version(), 1);
+        let uncommitted_events = auction_aggregate.get_uncommitted_events();
+        assert_eq!(uncommitted_events.len(), 1);
+        assert_eq!(uncommitted_events[0].get_event_id(), 2);
+    }
+
+    #[test]
+    fn events_carry_the_context_of_their_command() {
+        let clock = ManualClock::new(Utc::now());
+        let now = clock.now();
+        let mut auction_aggregate =
+            create_auction(1, english_auction_terms()).with_clock(Box::new(clock));
+        let request_id = Uuid::new_v4();
+        let context = CommandContext::new()
+            .with_actor(1)
+            .with_causation_id(request_id);
+
+        let events = auction_aggregate
+            .execute_in_context(AuctionCommand::StartAuction, context)
+            .unwrap();
+        let metadata = events[0].get_metadata();
+        assert_eq!(events[0].get_event_id(), 2);
+        assert_eq!(metadata.get_occurred_at(), now);
+        assert_eq!(metadata.get_actor_id(), Some(1));
+        assert_eq!(metadata.get_correlation_id(), context.get_correlation_id());
+        assert_eq!(metadata.get_causation_id(), Some(request_id));
+
+        // Every event gets an id of its own
+        let created_event_id = auction_aggregate.get_uncommitted_events()[0]
+            .get_metadata()
+            .get_event_id();
+        assert_ne!(metadata.get_event_id(), created_event_id);
+    }
+
+    #[test]
+    fn rejected_command_context_is_not_reused() {
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+        let context = CommandContext::new().with_actor(1);
+        assert!(auction_aggregate
+            .execute_in_context(AuctionCommand::StartAuction, context)
+            .is_err());
+
+        let events = offer_bid(&mut auction_aggregate, 2, sgd(10)).unwrap();
+        let metadata = events[0].get_metadata();
+        assert_eq!(metadata.get_actor_id(), None);
+        assert_ne!(metadata.get_correlation_id(), context.get_correlation_id());
+    }
+
+    #[test]
+    fn snapshot_with_newer_events_matches_full_replay() {
+        let alice = generate_user();
+        let bob = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+        place_proxy_bid(&mut auction_aggregate, alice.get_id(), sgd(40)).unwrap();
+        offer_bid(&mut auction_aggregate, bob.get_id(), sgd(15)).unwrap();
+
+        let json = serde_json::to_string(&auction_aggregate.take_snapshot()).unwrap();
+        let snapshot: AuctionSnapshot = serde_json::from_str(&json).unwrap();
+        assert!(snapshot.is_current());
+        assert_eq!(snapshot.get_version(), auction_aggregate.get_version());
+
+        // Later bid still runs into the proxy bid and the rules restored from the snapshot
+        let later_events = offer_bid(&mut auction_aggregate, bob.get_id(), sgd(30)).unwrap();
+        let mut restored = AuctionAggregate::from_snapshot(snapshot, later_events);
+        assert_eq!(
+            restored.get_committed_version(),
+            auction_aggregate.get_version()
+        );
+        assert_eq!(
+            restored.get_state().bids(),
+            auction_aggregate.get_state().bids()
+        );
+        assert_eq!(
+            offer_bid(&mut restored, bob.get_id(), sgd(36))
+                .unwrap_err()
+                .code(),
+            "minimum_bid_increment"
+        );
+    }
+
+    #[test]
+    fn bid_offer_command_emits_bid_offered_event() {
+        let user = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+
+        let events = offer_bid(&mut auction_aggregate, user.get_id(), sgd(10)).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].get_event_type(), AuctionEventType::BidOffered);
+        assert_eq!(events[0].get_event_id(), 3);
+        assert_eq!(
+            events[1].get_event_type(),
+            AuctionEventType::AuctionInProgress
+        );
+        assert_eq!(events[0].get_bid().unwrap().price, sgd(10));
+
+        // Rehydrating from the recorded events gives back the same bid history
+        let events = std::mem::take(&mut auction_aggregate.domain_events);
+        let rehydrated = AuctionAggregate::new(events);
+        assert_eq!(rehydrated.get_state().leading_bid().unwrap().price, sgd(10));
+    }
+
+    #[test]
+    fn rejected_command_leaves_aggregate_unchanged() {
+        let user = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(10)).unwrap();
+
+        // Rejected by the minimum increment rule
+        let rejection = offer_bid(&mut auction_aggregate, user.get_id(), sgd(12)).unwrap_err();
+        assert_eq!(rejection.code(), "minimum_bid_increment");
+        assert_eq!(auction_aggregate.domain_events.len(), 4);
+        assert_eq!(auction_aggregate.get_state().bids().len(), 1);
+    }
+
+    #[test]
+    fn invalid_bid_less_than_opening_bid() {
+        let user = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, user.get_id(), sgd(8)),
+            Err(AuctionError::BidRejected(BidRejection::BelowOpeningPrice {
+                opening_bid_price: sgd(10)
+            }))
+        );
+    }
+
+    #[test]
+    fn proxy_bid_outbids_literal_bid_by_minimum_increment() {
+        let alice = generate_user();
+        let bob = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+
+        place_proxy_bid(&mut auction_aggregate, alice.get_id(), sgd(50)).unwrap();
+        let state = auction_aggregate.get_state();
+        assert_eq!(state.leading_bid().unwrap().price.value, 10);
+
+        let events = offer_bid(&mut auction_aggregate, bob.get_id(), sgd(20)).unwrap();
+        // Bob's bid followed by the proxy's response
+        assert_eq!(events.len(), 2);
+        let leading_bid = auction_aggregate.get_state().leading_bid().unwrap();
+        assert_eq!(leading_bid.get_bidder_id(), alice.get_id());
+        assert_eq!(leading_bid.price.value, 25);
+    }
+
+    #[test]
+    fn proxy_bid_war_settles_above_runner_up_maximum() {
+        let alice = generate_user();
+        let bob = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+
+        place_proxy_bid(&mut auction_aggregate, alice.get_id(), sgd(100)).unwrap();
+        place_proxy_bid(&mut auction_aggregate, bob.get_id(), sgd(62)).unwrap();
+
+        let state = auction_aggregate.get_state();
+        let prices: Vec<u32> = state.bids().iter().map(|bid| bid.price.value).collect();
+        assert_eq!(prices, vec![10, 62, 67]);
+        assert_eq!(state.leading_bid().unwrap().get_bidder_id(), alice.get_id());
+        // Hidden maximum never shows up in the history
+        assert!(state.bids().iter().all(|bid| bid.price.value != 100));
+    }
+
+    #[test]
+    fn equal_proxy_maximums_won_by_earliest() {
+        let alice = generate_user();
+        let bob = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+
+        place_proxy_bid(&mut auction_aggregate, alice.get_id(), sgd(40)).unwrap();
+        place_proxy_bid(&mut auction_aggregate, bob.get_id(), sgd(40)).unwrap();
+
+        let leading_bid = auction_aggregate.get_state().leading_bid().unwrap();
+        assert_eq!(leading_bid.get_bidder_id(), alice.get_id());
+        assert_eq!(leading_bid.price.value, 40);
+    }
+
+    #[test]
+    fn auction_meeting_reserve_is_sold() {
+        let seller = generate_user();
+        let user = generate_user();
+        let terms = AuctionTerms {
+            reserve_price: Some(50),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(seller.get_id(), terms);
+
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(50)).unwrap();
+        assert!(auction_aggregate.get_state().is_reserve_met());
+        auction_aggregate
+            .execute(AuctionCommand::CloseAuction {
+                closed_by: seller.get_id(),
+                reason: String::from("Reserve met"),
+            })
+            .unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(50) })
+        );
+
+        // No more bids once closed
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, user.get_id(), sgd(60)),
+            Err(AuctionError::BidRejected(BidRejection::AuctionClosed))
+        );
+    }
+
+    #[test]
+    fn late_bid_extends_deadline() {
+        let user = generate_user();
+        let clock = ManualClock::new(Utc::now());
+        let ends_at = clock.now() + Duration::minutes(30);
+        let terms = AuctionTerms {
+            ends_at: Some(ends_at),
+            soft_close: Some(SoftClose {
+                window: Duration::minutes(5),
+                extension: Duration::minutes(2),
+            }),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(1, terms).with_clock(Box::new(clock.clone()));
+
+        // Outside of the soft-close window
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(10)).unwrap();
+        assert_eq!(auction_aggregate.get_state().get_ends_at(), Some(ends_at));
+
+        clock.advance(Duration::minutes(27));
+        let events = offer_bid(&mut auction_aggregate, user.get_id(), sgd(15)).unwrap();
+        let extended_ends_at = ends_at + Duration::minutes(2);
+        assert_eq!(
+            auction_aggregate.get_state().get_ends_at(),
+            Some(extended_ends_at)
+        );
+        let last_event = events.last().unwrap();
+        assert_eq!(
+            last_event.get_event_type(),
+            AuctionEventType::DeadlineExtended
+        );
+        assert_eq!(last_event.ends_at, Some(extended_ends_at));
+
+        clock.advance(Duration::minutes(5));
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, user.get_id(), sgd(20)),
+            Err(AuctionError::BidRejected(BidRejection::DeadlinePassed {
+                ends_at: extended_ends_at
+            }))
+        );
+    }
+
+    #[test]
+    fn headshot_bid_wins_auction_immediately() {
+        let alice = generate_user();
+        let bob = generate_user();
+        let terms = AuctionTerms {
+            headshot_bid_amount: Some(100),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(1, terms);
+
+        let events = offer_bid(&mut auction_aggregate, alice.get_id(), sgd(120)).unwrap();
+        assert_eq!(
+            events[0].get_event_type(),
+            AuctionEventType::HeadshotBidPlaced
+        );
+        let state = auction_aggregate.get_state();
+        let leading_bid = state.leading_bid().unwrap();
+        assert_eq!(leading_bid.get_bidder_id(), alice.get_id());
+        assert_eq!(leading_bid.price.value, 100);
+        assert!(state.is_won_by_headshot());
+
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, bob.get_id(), sgd(100)),
+            Err(AuctionError::BidRejected(
+                BidRejection::AuctionWonByHeadshot { price: sgd(100) }
+            ))
+        );
+    }
+
+    #[test]
+    fn proxy_reaching_headshot_wins_auction() {
+        let alice = generate_user();
+        let bob = generate_user();
+        let terms = AuctionTerms {
+            headshot_bid_amount: Some(100),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(1, terms);
+
+        place_proxy_bid(&mut auction_aggregate, alice.get_id(), sgd(80)).unwrap();
+        place_proxy_bid(&mut auction_aggregate, bob.get_id(), sgd(150)).unwrap();
+
+        let state = auction_aggregate.get_state();
+        let leading_bid = state.leading_bid().unwrap();
+        assert_eq!(leading_bid.get_bidder_id(), bob.get_id());
+        assert_eq!(leading_bid.price.value, 100);
+        assert!(state.is_won_by_headshot());
+    }
+
+    #[test]
+    fn configured_rules_replace_default_rules() {
+        let user = generate_user();
+        let config = BiddingRulesConfig::from_json(
+            r#"{ "rules": [
+                { "rule": "minimum_bid_increment", "min_increment": 10 },
+                { "rule": "maximum_bid", "maximum_bid": 50 }
+            ] }"#,
+        )
+        .unwrap();
+        let terms = AuctionTerms {
+            bidding_rules: Some(config),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(1, terms);
+        assert_eq!(
+            auction_aggregate.get_state().rule_codes(),
+            vec![
+                "seller_cannot_bid",
+                "same_bid_currency",
+                "minimum_bid_increment",
+                "maximum_bid"
+            ]
+        );
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, 1, sgd(20)),
+            Err(AuctionError::BidRejected(BidRejection::SellerCannotBid))
+        );
+
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(20)).unwrap();
+        let too_small = offer_bid(&mut auction_aggregate, user.get_id(), sgd(25));
+        match too_small {
+            Err(AuctionError::BidRejected(rejection)) => {
+                assert_eq!(rejection.next_valid_bid(), Some(sgd(30)))
+            }
+            _ => panic!("Expected bid to be rejected"),
+        }
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, user.get_id(), sgd(60)),
+            Err(AuctionError::BidRejected(BidRejection::AboveMaximumBid {
+                maximum_bid: sgd(50)
+            }))
+        );
+    }
+
+    #[test]
+    fn configured_rules_keep_bids_in_auction_currency() {
+        let config = BiddingRulesConfig::from_json(
+            r#"{ "rules": [{ "rule": "minimum_bid_increment", "min_increment": 1 }] }"#,
+        )
+        .unwrap();
+        let terms = AuctionTerms {
+            bidding_rules: Some(config),
+            ..english_auction_terms()
+        };
+        let mut auction_aggregate = start_auction(1, terms);
+        offer_bid(&mut auction_aggregate, 2, sgd(10)).unwrap();
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, 3, Price::new(Currency::MYR, 11)),
+            Err(AuctionError::BidRejected(BidRejection::CurrencyMismatch {
+                expected: Currency::SGD,
+                received: Currency::MYR
+            }))
+        );
+    }
+
+    #[test]
+    fn bid_below_leading_bid_suggests_next_minimum_bid() {
+        let user = generate_user();
+        let mut auction_aggregate = start_auction(1, english_auction_terms());
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(30)).unwrap();
+
+        for price in [sgd(12), sgd(30)] {
+            assert_eq!(
+                offer_bid(&mut auction_aggregate, user.get_id(), price),
+                Err(AuctionError::BidRejected(BidRejection::IncrementTooSmall {
+                    minimum_increment: 5,
+                    next_minimum_bid: sgd(35),
+                }))
+            );
+        }
+        assert_eq!(
+            auction_aggregate.get_state().next_minimum_bid(),
+            Some(sgd(35))
+        );
+    }
+
+    #[test]
+    fn next_minimum_bid_follows_increment_tiers() {
+        let user = generate_user();
+        let config = BiddingRulesConfig::new(vec![RuleSpec::TieredBidIncrement {
+            tiers: vec![
+                IncrementTier {
+                    from: 0,
+                    increment: 1,
+                },
+                IncrementTier {
+                    from: 50,
+                    increment: 5,
+                },
+            ],
+        }])
+        .unwrap();
+        let terms = AuctionTerms {
+            opening_bid_price: Some(sgd(5)),
+            bidding_rules: Some(config),
+            ..AuctionTerms::default()
+        };
+        let mut auction_aggregate = start_auction(1, terms);
+        assert_eq!(
+            auction_aggregate.get_state().next_minimum_bid(),
+            Some(sgd(5))
+        );
+
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(20)).unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().next_minimum_bid(),
+            Some(sgd(21))
+        );
+
+        offer_bid(&mut auction_aggregate, user.get_id(), sgd(50)).unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().next_minimum_bid(),
+            Some(sgd(55))
+        );
+    }
+
+    #[test]
+    fn seller_and_linked_accounts_cannot_bid() {
+        let seller = generate_user_with_contact("jane@gmail.com", "1111 1111");
+        let users = [
+            generate_user_with_contact("jane.shill@gmail.com", "1111 1111"),
+            generate_user_with_contact("john@gmail.com", "2222 2222"),
+        ];
+        let (shill_id, bidder_id) = (users[0].get_id(), users[1].get_id());
+        let auction_item = AuctionItem::new(1, String::from("Brass Birmingham"), seller.get_id());
+        let accounts = Arc::new(RegisteredAccounts::new(LinkedAccountRegistry::default()));
+        accounts.register(&seller);
+        accounts.register(&users[1]);
+        let mut auction_aggregate =
+            start_auction(auction_item.get_owner_id(), english_auction_terms())
+                .with_account_links(accounts.clone());
+
+        assert_eq!(
+            offer_bid(&mut auction_aggregate, seller.get_id(), sgd(10)),
+            Err(AuctionError::BidRejected(BidRejection::SellerCannotBid))
+        );
+        // Shill account found to be linked after the auction started
+        accounts.register(&users[0]);
+        let Err(AuctionError::BidRejected(shill_bid)) =
+            place_proxy_bid(&mut auction_aggregate, shill_id, sgd(50))
+        else {
+            panic!("Expected shill bid to be rejected");
+        };
+        assert_eq!(
+            shill_bid,
+            BidRejection::LinkedToSeller {
+                link: AccountLink::SamePhoneNumber
+            }
+        );
+        assert_eq!(shill_bid.code(), SELLER_CANNOT_BID);
+        let moderation_record =
+            ModerationRecord::for_rejection(1, shill_id, &shill_bid, Utc::now());
+        assert_eq!(moderation_record.unwrap().bidder_id, shill_id);
+
+        offer_bid(&mut auction_aggregate, bidder_id, sgd(10)).unwrap();
+    }
 }
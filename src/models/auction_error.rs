@@ -0,0 +1,133 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    auction::AuctionStatus, auction_aggregate::AuctionId, bid_rejection::BidRejection, user::UserId,
+};
+
+// Reasons an auction command is turned down. A rejected command leaves the auction untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuctionError {
+    BidRejected(BidRejection),
+    AlreadyCreated {
+        auction_id: AuctionId,
+    },
+    NotCreated,
+    // Command is not allowed at this point of the auction's lifecycle
+    InvalidStatus {
+        status: AuctionStatus,
+        command: &'static str,
+    },
+    AlreadyEnded,
+    DeadlineNotReached {
+        ends_at: DateTime<Utc>,
+    },
+    NoAskingPrice,
+    // Price of a descending price auction drops on a schedule of its own
+    NoPriceDrop,
+    PriceDropNotDue {
+        due_at: DateTime<Utc>,
+    },
+    InvalidTerms {
+        reason: &'static str,
+    },
+    // Dutch auctions are only bought by accepting the current price
+    DutchAuctionOnly,
+    NotSealedBid,
+    // Sealed bid auctions only take sealed bids
+    SealedBidsOnly,
+    // Another bidder has already submitted the same commitment
+    CommitmentTaken,
+    NoSealedBid {
+        bidder_id: UserId,
+    },
+    // Revealed price and nonce do not hash to the bidder's commitment
+    CommitmentMismatch,
+    NonceTooShort {
+        minimum_length: usize,
+    },
+    RevealWindowClosed {
+        ends_at: DateTime<Utc>,
+    },
+    // Only the seller may close their auction early
+    NotSeller {
+        user_id: UserId,
+    },
+}
+impl AuctionError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BidRejected(rejection) => rejection.code(),
+            Self::AlreadyCreated { .. } => "auction_already_created",
+            Self::NotCreated => "auction_not_created",
+            Self::InvalidStatus { .. } => "invalid_auction_status",
+            Self::AlreadyEnded => "auction_already_ended",
+            Self::DeadlineNotReached { .. } => "auction_deadline_not_reached",
+            Self::NoAskingPrice => "auction_has_no_asking_price",
+            Self::NoPriceDrop => "auction_has_no_price_drop",
+            Self::PriceDropNotDue { .. } => "auction_price_drop_not_due",
+            Self::InvalidTerms { .. } => "invalid_auction_terms",
+            Self::DutchAuctionOnly => "auction_takes_current_price_only",
+            Self::NotSealedBid => "auction_not_sealed_bid",
+            Self::SealedBidsOnly => "auction_takes_sealed_bids_only",
+            Self::CommitmentTaken => "bid_commitment_taken",
+            Self::NoSealedBid { .. } => "no_sealed_bid",
+            Self::CommitmentMismatch => "bid_commitment_mismatch",
+            Self::NonceTooShort { .. } => "bid_nonce_too_short",
+            Self::RevealWindowClosed { .. } => "reveal_window_closed",
+            Self::NotSeller { .. } => "not_auction_seller",
+        }
+    }
+}
+impl From<BidRejection> for AuctionError {
+    fn from(rejection: BidRejection) -> Self {
+        Self::BidRejected(rejection)
+    }
+}
+impl fmt::Display for AuctionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BidRejected(rejection) => write!(f, "bid rejected: {}", rejection),
+            Self::AlreadyCreated { auction_id } => {
+                write!(f, "auction {} has already been created", auction_id)
+            }
+            Self::NotCreated => write!(f, "auction has not been created"),
+            Self::InvalidStatus { status, command } => {
+                write!(f, "cannot {} an auction that is {:?}", command, status)
+            }
+            Self::AlreadyEnded => write!(f, "auction has already ended"),
+            Self::DeadlineNotReached { ends_at } => {
+                write!(f, "auction deadline at {} has not been reached", ends_at)
+            }
+            Self::NoAskingPrice => write!(f, "auction has no asking price to accept"),
+            Self::NoPriceDrop => write!(f, "auction's price does not drop"),
+            Self::PriceDropNotDue { due_at } => {
+                write!(f, "auction's next price drop is not due until {}", due_at)
+            }
+            Self::InvalidTerms { reason } => write!(f, "invalid auction terms: {}", reason),
+            Self::DutchAuctionOnly => {
+                write!(f, "auction is only bought by accepting its current price")
+            }
+            Self::NotSealedBid => write!(f, "auction does not take sealed bids"),
+            Self::SealedBidsOnly => write!(f, "auction only takes sealed bids"),
+            Self::CommitmentTaken => write!(f, "bid commitment has already been submitted"),
+            Self::NoSealedBid { bidder_id } => {
+                write!(f, "user {} has not submitted a sealed bid", bidder_id)
+            }
+            Self::CommitmentMismatch => {
+                write!(f, "revealed bid does not match the submitted commitment")
+            }
+            Self::NonceTooShort { minimum_length } => {
+                write!(f, "nonce must be at least {} characters", minimum_length)
+            }
+            Self::RevealWindowClosed { ends_at } => {
+                write!(f, "reveal window closed at {}", ends_at)
+            }
+            Self::NotSeller { user_id } => {
+                write!(f, "user {} is not the seller of this auction", user_id)
+            }
+        }
+    }
+}
+impl std::error::Error for AuctionError {}
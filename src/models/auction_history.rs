@@ -0,0 +1,252 @@
+use chrono::{DateTime, Utc};
+
+use super::{
+    auction::AuctionStatus,
+    auction_aggregate::{AuctionAggregate, AuctionEvent, EventId},
+    bid::Bid,
+    user::UserId,
+};
+
+// Moment in an auction's history, either when its events occurred or a version of its stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointInTime {
+    At(DateTime<Utc>),
+    Version(EventId),
+}
+impl PointInTime {
+    // Version of the stream at this point. A moment resolves to the last event that had occurred
+    // by then, looking at every event, since timestamps are not guaranteed to grow along the
+    // stream when clocks are skewed. Stream order is what counts, so the events before it are
+    // part of the history at that moment whatever their own timestamps.
+    pub fn resolve(&self, events: &[AuctionEvent]) -> EventId {
+        match self {
+            Self::At(at) => events
+                .iter()
+                .filter(|event| event.get_metadata().get_occurred_at() <= *at)
+                .map(|event| event.get_event_id())
+                .max()
+                .unwrap_or(0),
+            Self::Version(version) => *version,
+        }
+    }
+}
+
+// What the auction looked like at a point in its history
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuctionStateAt {
+    version: EventId,
+    status: Option<AuctionStatus>,
+    leading_bid: Option<Bid>,
+    // Bidders with a visible bid, in the order they first bid
+    bidders: Vec<UserId>,
+    ends_at: Option<DateTime<Utc>>,
+}
+impl AuctionStateAt {
+    pub fn new(auction_aggregate: &AuctionAggregate) -> Self {
+        let state = auction_aggregate.get_state();
+        let mut bidders = vec![];
+        for bid in state.bids() {
+            if !bidders.contains(&bid.get_bidder_id()) {
+                bidders.push(bid.get_bidder_id());
+            }
+        }
+        Self {
+            version: auction_aggregate.get_version(),
+            status: state.get_status(),
+            leading_bid: state.leading_bid().cloned(),
+            bidders,
+            ends_at: state.get_ends_at(),
+        }
+    }
+
+    pub fn get_version(&self) -> EventId {
+        self.version
+    }
+
+    pub fn get_status(&self) -> Option<AuctionStatus> {
+        self.status
+    }
+
+    pub fn get_leading_bid(&self) -> Option<&Bid> {
+        self.leading_bid.as_ref()
+    }
+
+    pub fn get_bidders(&self) -> &[UserId] {
+        &self.bidders
+    }
+
+    pub fn get_ends_at(&self) -> Option<DateTime<Utc>> {
+        self.ends_at
+    }
+}
+
+// Value before and after a change
+pub type Change<T> = (T, T);
+
+// Changes to an auction between two points in its history, none for values that did not
+// change
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuctionStateDiff {
+    from: AuctionStateAt,
+    to: AuctionStateAt,
+    // Events raised in between, in the order they were raised
+    events: Vec<AuctionEvent>,
+}
+impl AuctionStateDiff {
+    pub fn new(from: AuctionStateAt, to: AuctionStateAt, events: Vec<AuctionEvent>) -> Self {
+        Self { from, to, events }
+    }
+
+    pub fn get_from(&self) -> &AuctionStateAt {
+        &self.from
+    }
+
+    pub fn get_to(&self) -> &AuctionStateAt {
+        &self.to
+    }
+
+    pub fn get_events(&self) -> &[AuctionEvent] {
+        &self.events
+    }
+
+    pub fn status_change(&self) -> Option<Change<Option<AuctionStatus>>> {
+        (self.from.status != self.to.status).then_some((self.from.status, self.to.status))
+    }
+
+    pub fn leading_bid_change(&self) -> Option<Change<Option<&Bid>>> {
+        (self.from.leading_bid != self.to.leading_bid)
+            .then_some((self.from.get_leading_bid(), self.to.get_leading_bid()))
+    }
+
+    pub fn ends_at_change(&self) -> Option<Change<Option<DateTime<Utc>>>> {
+        (self.from.ends_at != self.to.ends_at).then_some((self.from.ends_at, self.to.ends_at))
+    }
+
+    // Bidders with a visible bid at the later point only
+    pub fn new_bidders(&self) -> Vec<UserId> {
+        self.to
+            .bidders
+            .iter()
+            .filter(|bidder_id| !self.from.bidders.contains(bidder_id))
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use crate::{
+        models::{
+            auction_aggregate::{AuctionCommand, AuctionTerms},
+            clock::ManualClock,
+        },
+        test_support::sgd,
+    };
+
+    use super::*;
+
+    #[test]
+    fn rehydrates_the_auction_as_of_a_moment() {
+        let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 11, 30, 21, 0, 0).unwrap());
+        let mut auction_aggregate =
+            AuctionAggregate::new(vec![]).with_clock(Box::new(clock.clone()));
+        let terms = AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            ..AuctionTerms::default()
+        };
+        let commands = vec![
+            AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms,
+            },
+            AuctionCommand::StartAuction,
+            AuctionCommand::MakeBidOffer {
+                bid: Bid::new(2, sgd(10)),
+            },
+            AuctionCommand::MakeBidOffer {
+                bid: Bid::new(3, sgd(20)),
+            },
+        ];
+        // Bids come in at 21:30 and 22:00
+        for command in commands {
+            if matches!(command, AuctionCommand::MakeBidOffer { .. }) {
+                clock.advance(Duration::minutes(30));
+            }
+            auction_aggregate.execute(command).unwrap();
+        }
+        let events = auction_aggregate.get_uncommitted_events().to_vec();
+
+        let at = Utc.with_ymd_and_hms(2024, 11, 30, 21, 59, 30).unwrap();
+        let earlier = AuctionStateAt::new(&AuctionAggregate::as_of(
+            events.clone(),
+            PointInTime::At(at),
+        ));
+        assert_eq!(earlier.get_version(), 4);
+        assert_eq!(earlier.get_status(), Some(AuctionStatus::InProgress));
+        assert_eq!(earlier.get_leading_bid().unwrap().get_bidder_id(), 2);
+        assert_eq!(earlier.get_bidders(), &[2]);
+
+        let later = AuctionStateAt::new(&AuctionAggregate::as_of(
+            events.clone(),
+            PointInTime::Version(5),
+        ));
+        assert_eq!(later, AuctionStateAt::new(&auction_aggregate));
+        let diff = AuctionStateDiff::new(earlier, later, events[4..].to_vec());
+        assert_eq!(diff.status_change(), None);
+        let (before, after) = diff.leading_bid_change().unwrap();
+        assert_eq!(before.unwrap().price, sgd(10));
+        assert_eq!(after.unwrap().price, sgd(20));
+        assert_eq!(diff.new_bidders(), vec![3]);
+        assert_eq!(diff.get_events().len(), 1);
+    }
+
+    #[test]
+    fn moment_resolves_past_events_with_skewed_timestamps() {
+        let clock = ManualClock::new(Utc.with_ymd_and_hms(2024, 11, 30, 21, 0, 0).unwrap());
+        let mut auction_aggregate =
+            AuctionAggregate::new(vec![]).with_clock(Box::new(clock.clone()));
+        let terms = AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            ..AuctionTerms::default()
+        };
+        auction_aggregate
+            .execute(AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms,
+            })
+            .unwrap();
+        // Clock jumps ahead for the start, then falls back to 21:30 for the bid
+        clock.advance(Duration::hours(2));
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        clock.advance(Duration::minutes(-90));
+        auction_aggregate
+            .execute(AuctionCommand::MakeBidOffer {
+                bid: Bid::new(2, sgd(10)),
+            })
+            .unwrap();
+        let events = auction_aggregate.get_uncommitted_events().to_vec();
+
+        // Bid had occurred by 21:45 and the start came before it in the stream
+        let at = Utc.with_ymd_and_hms(2024, 11, 30, 21, 45, 0).unwrap();
+        let state_at = AuctionStateAt::new(&AuctionAggregate::as_of(
+            events.clone(),
+            PointInTime::At(at),
+        ));
+        assert_eq!(state_at, AuctionStateAt::new(&auction_aggregate));
+        assert_eq!(state_at.get_leading_bid().unwrap().get_bidder_id(), 2);
+
+        let before_bids = Utc.with_ymd_and_hms(2024, 11, 30, 21, 15, 0).unwrap();
+        let state_at = AuctionStateAt::new(&AuctionAggregate::as_of(
+            events,
+            PointInTime::At(before_bids),
+        ));
+        assert_eq!(state_at.get_version(), 1);
+        assert_eq!(state_at.get_status(), Some(AuctionStatus::Created));
+    }
+}
@@ -1,19 +1,23 @@
-use crate::models::user::User;
+use crate::models::user::UserId;
 
 pub type AuctionItemId = u32;
 
-pub struct AuctionItem<'a> {
+pub struct AuctionItem {
     id: AuctionItemId,
     name: String,
-    owner: &'a User,
+    owner_id: UserId,
 }
 
-impl<'a> AuctionItem<'a> {
-    pub fn new(id: AuctionItemId, name: String, owner: &'a User) -> Self {
-        Self { id, name, owner }
+impl AuctionItem {
+    pub fn new(id: AuctionItemId, name: String, owner_id: UserId) -> Self {
+        Self { id, name, owner_id }
     }
 
     pub fn get_id(&self) -> AuctionItemId {
         self.id
     }
+
+    pub fn get_owner_id(&self) -> UserId {
+        self.owner_id
+    }
 }
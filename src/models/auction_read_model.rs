@@ -0,0 +1,232 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    auction::AuctionStatus,
+    auction_aggregate::{AuctionEvent, AuctionEventType, AuctionId, EventId},
+    price::Price,
+    user::UserId,
+};
+
+// Denormalized view of an auction for listings, kept up to date from its events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuctionSummary {
+    auction_id: AuctionId,
+    seller_id: Option<UserId>,
+    status: AuctionStatus,
+    // Leading bid, or the opening or asking price while there is none
+    current_price: Option<Price>,
+    bid_count: u32,
+    ends_at: Option<DateTime<Utc>>,
+    // Version of the auction's stream the summary is up to date with
+    version: EventId,
+}
+impl AuctionSummary {
+    // Summary of a newly created auction, none for any other event
+    pub fn created(event: &AuctionEvent) -> Option<Self> {
+        if event.get_event_type() != AuctionEventType::AuctionCreated {
+            return None;
+        }
+        Some(Self {
+            auction_id: event.get_auction_id(),
+            seller_id: event.get_seller_id(),
+            status: AuctionStatus::Created,
+            current_price: event.get_price().or(event.get_opening_bid_price()),
+            bid_count: 0,
+            ends_at: event.get_ends_at(),
+            version: event.get_event_id(),
+        })
+    }
+
+    // Events at or below the summary's version were applied already and are skipped, so
+    // replaying events is harmless
+    pub fn apply(&mut self, event: &AuctionEvent) {
+        if event.get_event_id() <= self.version {
+            return;
+        }
+        match event.get_event_type() {
+            AuctionEventType::AuctionCreated => {}
+            AuctionEventType::AuctionStarted => self.status = AuctionStatus::Started,
+            AuctionEventType::AuctionInProgress => self.status = AuctionStatus::InProgress,
+            AuctionEventType::AuctionEnding => self.status = AuctionStatus::Ending,
+            AuctionEventType::AuctionEnded => {
+                // Winning sealed bid, the only one ever listed
+                self.record_bid(event);
+                self.status = AuctionStatus::Ended;
+            }
+            AuctionEventType::AuctionClosed => self.status = AuctionStatus::Closed,
+            AuctionEventType::BidOffered => self.record_bid(event),
+            AuctionEventType::HeadshotBidPlaced | AuctionEventType::CurrentPriceAccepted => {
+                self.record_bid(event);
+                self.status = AuctionStatus::Ended;
+            }
+            // Secret maximums never show in listings
+            AuctionEventType::ProxyBidRegistered => {}
+            AuctionEventType::DeadlineExtended => self.ends_at = event.get_ends_at(),
+            AuctionEventType::PriceDropped => {
+                if self.bid_count == 0 {
+                    self.current_price = event.get_price();
+                }
+            }
+            AuctionEventType::SealedBidSubmitted | AuctionEventType::SealedBidRevealed => {}
+            AuctionEventType::SealedBiddingClosed => {
+                self.status = AuctionStatus::Revealing;
+                self.ends_at = event.get_ends_at();
+            }
+        }
+        self.version = event.get_event_id();
+    }
+
+    fn record_bid(&mut self, event: &AuctionEvent) {
+        if let Some(bid) = event.get_bid() {
+            self.bid_count += 1;
+            self.current_price = Some(bid.price);
+        }
+    }
+
+    pub fn get_auction_id(&self) -> AuctionId {
+        self.auction_id
+    }
+
+    pub fn get_seller_id(&self) -> Option<UserId> {
+        self.seller_id
+    }
+
+    pub fn get_status(&self) -> AuctionStatus {
+        self.status
+    }
+
+    pub fn get_current_price(&self) -> Option<Price> {
+        self.current_price
+    }
+
+    pub fn get_bid_count(&self) -> u32 {
+        self.bid_count
+    }
+
+    pub fn get_ends_at(&self) -> Option<DateTime<Utc>> {
+        self.ends_at
+    }
+
+    pub fn get_version(&self) -> EventId {
+        self.version
+    }
+}
+
+// Visible bid of an auction, proxy maximums are never part of the history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BidHistoryEntry {
+    auction_id: AuctionId,
+    // Version of the event that recorded the bid, orders the history
+    version: EventId,
+    bidder_id: UserId,
+    price: Price,
+    placed_at: DateTime<Utc>,
+}
+impl BidHistoryEntry {
+    pub fn from_event(event: &AuctionEvent) -> Option<Self> {
+        match event.get_event_type() {
+            AuctionEventType::BidOffered
+            | AuctionEventType::HeadshotBidPlaced
+            | AuctionEventType::CurrentPriceAccepted
+            // Carries the winning bid of a sealed bid auction
+            | AuctionEventType::AuctionEnded => {
+                let bid = event.get_bid()?;
+                Some(Self {
+                    auction_id: event.get_auction_id(),
+                    version: event.get_event_id(),
+                    bidder_id: bid.get_bidder_id(),
+                    price: bid.price,
+                    placed_at: bid.get_created_at(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_auction_id(&self) -> AuctionId {
+        self.auction_id
+    }
+
+    pub fn get_version(&self) -> EventId {
+        self.version
+    }
+
+    pub fn get_bidder_id(&self) -> UserId {
+        self.bidder_id
+    }
+
+    pub fn get_price(&self) -> Price {
+        self.price
+    }
+
+    pub fn get_placed_at(&self) -> DateTime<Utc> {
+        self.placed_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            auction_aggregate::{AuctionAggregate, AuctionCommand, AuctionTerms},
+            bid::Bid,
+        },
+        test_support::sgd,
+    };
+
+    use super::*;
+
+    #[test]
+    fn summary_follows_bids_and_status() {
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        let terms = AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            ..AuctionTerms::default()
+        };
+        let commands = vec![
+            AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms,
+            },
+            AuctionCommand::StartAuction,
+            AuctionCommand::PlaceProxyBid {
+                bid: Bid::new(2, sgd(30)),
+            },
+            AuctionCommand::MakeBidOffer {
+                bid: Bid::new(3, sgd(15)),
+            },
+        ];
+        for command in commands {
+            auction_aggregate.execute(command).unwrap();
+        }
+        let events = auction_aggregate.get_uncommitted_events();
+
+        let mut summary = AuctionSummary::created(&events[0]).unwrap();
+        assert_eq!(summary.get_current_price(), Some(sgd(10)));
+        for event in events {
+            summary.apply(event);
+        }
+        // Replayed events are skipped
+        for event in events {
+            summary.apply(event);
+        }
+        let leading_bid = auction_aggregate.get_state().leading_bid().unwrap();
+        assert_eq!(summary.get_status(), AuctionStatus::InProgress);
+        assert_eq!(summary.get_current_price(), Some(leading_bid.price));
+        assert_eq!(
+            summary.get_bid_count(),
+            auction_aggregate.get_state().bids().len() as u32
+        );
+        assert_eq!(summary.get_version(), auction_aggregate.get_version());
+
+        // Only visible bids make it into the history
+        let bid_history: Vec<BidHistoryEntry> = events
+            .iter()
+            .filter_map(BidHistoryEntry::from_event)
+            .collect();
+        assert_eq!(bid_history.len(), summary.get_bid_count() as usize);
+        assert_eq!(bid_history.last().unwrap().get_bidder_id(), 2);
+    }
+}
@@ -1,23 +1,58 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-use super::price::Price;
+use super::{price::Price, user::UserId};
 
-#[derive(Debug, Clone)]
+// Owned by value so it can be sent across tasks, stored and carried in events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Bid {
+    bidder_id: UserId,
     created_at: DateTime<Utc>,
     increment: Option<u32>,
     pub price: Price,
 }
 impl Bid {
-    pub fn new(price: Price) -> Self {
+    pub fn new(bidder_id: UserId, price: Price) -> Self {
         Self {
+            bidder_id,
             created_at: Utc::now(),
             increment: None,
             price,
         }
     }
 
-    fn set_increment(&mut self, increment: u32) {
+    pub fn get_bidder_id(&self) -> UserId {
+        self.bidder_id
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn get_increment(&self) -> Option<u32> {
+        self.increment
+    }
+
+    pub(crate) fn set_increment(&mut self, increment: u32) {
         self.increment = Some(increment);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::price::Currency;
+
+    use super::*;
+
+    #[test]
+    fn bid_round_trips_through_json() {
+        let mut bid = Bid::new(7, Price::new(Currency::SGD, 25));
+        bid.set_increment(5);
+
+        let json = serde_json::to_string(&bid).expect("Failed to serialize");
+        let deserialized: Bid = serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(deserialized.get_bidder_id(), 7);
+        assert_eq!(deserialized.get_increment(), Some(5));
+        assert_eq!(deserialized.price, bid.price);
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    linked_accounts::AccountLink,
+    price::{Currency, Price},
+};
+
+// Stable codes of the bidding rules, safe for clients to match on
+pub const SAME_BID_CURRENCY: &str = "same_bid_currency";
+pub const MINIMUM_BID_INCREMENT: &str = "minimum_bid_increment";
+pub const ALLOWED_CURRENCIES: &str = "allowed_currencies";
+pub const MAXIMUM_BID: &str = "maximum_bid";
+pub const BIDDER_ALLOW_LIST: &str = "bidder_allow_list";
+pub const TIERED_BID_INCREMENT: &str = "tiered_bid_increment";
+pub const SELLER_CANNOT_BID: &str = "seller_cannot_bid";
+
+// Reasons a bid is turned down, carrying what clients need to render their own message and
+// suggest the next bid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BidRejection {
+    CurrencyMismatch {
+        expected: Currency,
+        received: Currency,
+    },
+    CurrencyNotAllowed {
+        allowed: Vec<Currency>,
+        received: Currency,
+    },
+    IncrementTooSmall {
+        minimum_increment: u32,
+        next_minimum_bid: Price,
+    },
+    AboveMaximumBid {
+        maximum_bid: Price,
+    },
+    BidderNotAllowed,
+    SellerCannotBid,
+    LinkedToSeller {
+        link: AccountLink,
+    },
+    BelowOpeningPrice {
+        opening_bid_price: Price,
+    },
+    AuctionNotStarted,
+    AuctionClosed,
+    DeadlinePassed {
+        ends_at: DateTime<Utc>,
+    },
+    AuctionWonByHeadshot {
+        price: Price,
+    },
+}
+impl BidRejection {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::CurrencyMismatch { .. } => SAME_BID_CURRENCY,
+            Self::CurrencyNotAllowed { .. } => ALLOWED_CURRENCIES,
+            Self::IncrementTooSmall { .. } => MINIMUM_BID_INCREMENT,
+            Self::AboveMaximumBid { .. } => MAXIMUM_BID,
+            Self::BidderNotAllowed => BIDDER_ALLOW_LIST,
+            Self::SellerCannotBid | Self::LinkedToSeller { .. } => SELLER_CANNOT_BID,
+            Self::BelowOpeningPrice { .. } => "below_opening_bid",
+            Self::AuctionNotStarted => "auction_not_started",
+            Self::AuctionClosed => "auction_closed",
+            Self::DeadlinePassed { .. } => "auction_deadline_passed",
+            Self::AuctionWonByHeadshot { .. } => "auction_won_by_headshot",
+        }
+    }
+
+    // Possible shill bidding, to be looked at by a moderator
+    pub fn needs_moderation(&self) -> bool {
+        matches!(self, Self::SellerCannotBid | Self::LinkedToSeller { .. })
+    }
+
+    // Lowest bid that would get past this rejection, if bidding is still possible at all
+    pub fn next_valid_bid(&self) -> Option<Price> {
+        match self {
+            Self::IncrementTooSmall {
+                next_minimum_bid, ..
+            } => Some(*next_minimum_bid),
+            Self::BelowOpeningPrice { opening_bid_price } => Some(*opening_bid_price),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for BidRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CurrencyMismatch { expected, received } => write!(
+                f,
+                "bid's price currency {:?} does not match with set currency {:?}",
+                received, expected
+            ),
+            Self::CurrencyNotAllowed { allowed, received } => write!(
+                f,
+                "bid's price currency {:?} is not one of the allowed currencies {:?}",
+                received, allowed
+            ),
+            Self::IncrementTooSmall {
+                minimum_increment,
+                next_minimum_bid,
+            } => write!(
+                f,
+                "bid's increment must be at least {}, bid at least {:?} {}",
+                minimum_increment, next_minimum_bid.currency, next_minimum_bid.value
+            ),
+            Self::AboveMaximumBid { maximum_bid } => write!(
+                f,
+                "bid has exceeded the maximum bid value of {:?} {}",
+                maximum_bid.currency, maximum_bid.value
+            ),
+            Self::BidderNotAllowed => write!(f, "bidder is not allowed to bid on this auction"),
+            Self::SellerCannotBid => write!(f, "seller cannot bid on their own auction"),
+            Self::LinkedToSeller { link } => write!(
+                f,
+                "bidder's account is linked to the seller's account ({:?})",
+                link
+            ),
+            Self::BelowOpeningPrice { opening_bid_price } => write!(
+                f,
+                "first bid must be at least as high as the stipulated opening bid of {:?} {}",
+                opening_bid_price.currency, opening_bid_price.value
+            ),
+            Self::AuctionNotStarted => write!(f, "auction is not open for bids yet"),
+            Self::AuctionClosed => write!(f, "auction has already closed"),
+            Self::DeadlinePassed { ends_at } => {
+                write!(f, "auction deadline has passed at {}", ends_at)
+            }
+            Self::AuctionWonByHeadshot { price } => write!(
+                f,
+                "auction already won by a headshot bid of {:?} {}",
+                price.currency, price.value
+            ),
+        }
+    }
+}
+impl std::error::Error for BidRejection {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejection_suggests_next_valid_bid() {
+        let rejection = BidRejection::IncrementTooSmall {
+            minimum_increment: 5,
+            next_minimum_bid: Price::new(Currency::SGD, 25),
+        };
+        assert_eq!(rejection.code(), MINIMUM_BID_INCREMENT);
+        assert_eq!(
+            rejection.next_valid_bid(),
+            Some(Price::new(Currency::SGD, 25))
+        );
+        assert_eq!(BidRejection::AuctionClosed.next_valid_bid(), None);
+    }
+}
@@ -0,0 +1,509 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    bid::Bid,
+    bid_rejection::{
+        BidRejection, ALLOWED_CURRENCIES, BIDDER_ALLOW_LIST, MAXIMUM_BID, MINIMUM_BID_INCREMENT,
+        SAME_BID_CURRENCY, SELLER_CANNOT_BID, TIERED_BID_INCREMENT,
+    },
+    price::{Currency, Price},
+    user::UserId,
+};
+
+pub trait Rule: Debug + Send + Sync {
+    // Stable, machine-readable name of the rule
+    fn code(&self) -> &'static str;
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection>;
+}
+
+#[derive(Debug)]
+struct SameBidCurrency {
+    currency_to_follow: Currency,
+}
+impl Rule for SameBidCurrency {
+    fn code(&self) -> &'static str {
+        SAME_BID_CURRENCY
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        if bid.price.currency != self.currency_to_follow {
+            return Err(BidRejection::CurrencyMismatch {
+                expected: self.currency_to_follow,
+                received: bid.price.currency,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct AllowedCurrencies {
+    currencies: Vec<Currency>,
+}
+impl Rule for AllowedCurrencies {
+    fn code(&self) -> &'static str {
+        ALLOWED_CURRENCIES
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        if !self.currencies.contains(&bid.price.currency) {
+            return Err(BidRejection::CurrencyNotAllowed {
+                allowed: self.currencies.clone(),
+                received: bid.price.currency,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct MinimumBidIncrement {
+    min_increment: u32,
+}
+impl Rule for MinimumBidIncrement {
+    fn code(&self) -> &'static str {
+        MINIMUM_BID_INCREMENT
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        let increment = bid.get_increment().unwrap();
+        if increment < self.min_increment {
+            let previous_price = bid.price.value - increment;
+            return Err(BidRejection::IncrementTooSmall {
+                minimum_increment: self.min_increment,
+                next_minimum_bid: Price::new(
+                    bid.price.currency,
+                    previous_price + self.min_increment,
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+// Increment required from `from` upwards, until the next tier starts
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IncrementTier {
+    pub from: u32,
+    pub increment: u32,
+}
+
+// Increment depends on the current price, e.g. below 50 -> 1, 50 to 200 -> 5, above 200 -> 10
+#[derive(Debug)]
+struct TieredBidIncrement {
+    tiers: Vec<IncrementTier>,
+}
+impl Rule for TieredBidIncrement {
+    fn code(&self) -> &'static str {
+        TIERED_BID_INCREMENT
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        let increment = bid.get_increment().unwrap();
+        let previous_price = bid.price.value - increment;
+        let min_increment = increment_for_price(&self.tiers, previous_price);
+        if increment < min_increment {
+            return Err(BidRejection::IncrementTooSmall {
+                minimum_increment: min_increment,
+                next_minimum_bid: Price::new(bid.price.currency, previous_price + min_increment),
+            });
+        }
+        Ok(())
+    }
+}
+
+// Tiers are validated to be sorted and to start from 0
+fn increment_for_price(tiers: &[IncrementTier], price: u32) -> u32 {
+    tiers
+        .iter()
+        .rev()
+        .find(|tier| tier.from <= price)
+        .map_or(1, |tier| tier.increment)
+}
+
+#[derive(Debug)]
+struct MaximumBid {
+    maximum_bid: u32,
+}
+impl Rule for MaximumBid {
+    fn code(&self) -> &'static str {
+        MAXIMUM_BID
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        if bid.price.value > self.maximum_bid {
+            return Err(BidRejection::AboveMaximumBid {
+                maximum_bid: Price::new(bid.price.currency, self.maximum_bid),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct BidderAllowList {
+    bidder_ids: Vec<UserId>,
+}
+impl Rule for BidderAllowList {
+    fn code(&self) -> &'static str {
+        BIDDER_ALLOW_LIST
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        if !self.bidder_ids.contains(&bid.get_bidder_id()) {
+            return Err(BidRejection::BidderNotAllowed);
+        }
+        Ok(())
+    }
+}
+
+// Keeps the seller from bidding up their own item
+#[derive(Debug)]
+struct SellerCannotBid {
+    seller_id: UserId,
+}
+impl Rule for SellerCannotBid {
+    fn code(&self) -> &'static str {
+        SELLER_CANNOT_BID
+    }
+
+    fn enforce(&self, bid: &Bid) -> Result<(), BidRejection> {
+        if bid.get_bidder_id() == self.seller_id {
+            return Err(BidRejection::SellerCannotBid);
+        }
+        Ok(())
+    }
+}
+
+// Serializable description of a single bidding rule, e.g.
+// `{ "rule": "minimum_bid_increment", "min_increment": 5 }`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum RuleSpec {
+    SameBidCurrency { currency: Currency },
+    AllowedCurrencies { currencies: Vec<Currency> },
+    MinimumBidIncrement { min_increment: u32 },
+    TieredBidIncrement { tiers: Vec<IncrementTier> },
+    MaximumBid { maximum_bid: u32 },
+    BidderAllowList { bidder_ids: Vec<UserId> },
+    // Accounts linked to the seller are looked up when they bid, see `AccountLinks`
+    SellerCannotBid { seller_id: UserId },
+}
+impl RuleSpec {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::SameBidCurrency { .. } => SAME_BID_CURRENCY,
+            Self::AllowedCurrencies { .. } => ALLOWED_CURRENCIES,
+            Self::MinimumBidIncrement { .. } => MINIMUM_BID_INCREMENT,
+            Self::TieredBidIncrement { .. } => TIERED_BID_INCREMENT,
+            Self::MaximumBid { .. } => MAXIMUM_BID,
+            Self::BidderAllowList { .. } => BIDDER_ALLOW_LIST,
+            Self::SellerCannotBid { .. } => SELLER_CANNOT_BID,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::AllowedCurrencies { currencies } if currencies.is_empty() => {
+                Err("allowed currencies rule needs at least one currency".into())
+            }
+            Self::MinimumBidIncrement { min_increment: 0 } => {
+                Err("minimum bid increment must be greater than zero".into())
+            }
+            Self::TieredBidIncrement { tiers } => {
+                if tiers.first().map_or(true, |tier| tier.from != 0) {
+                    return Err("increment tiers must start from a price of 0".into());
+                }
+                if tiers.windows(2).any(|pair| pair[0].from >= pair[1].from) {
+                    return Err("increment tiers must be in ascending order of price".into());
+                }
+                if tiers.iter().any(|tier| tier.increment == 0) {
+                    return Err("tier increments must be greater than zero".into());
+                }
+                Ok(())
+            }
+            Self::MaximumBid { maximum_bid: 0 } => {
+                Err("maximum bid must be greater than zero".into())
+            }
+            Self::BidderAllowList { bidder_ids } if bidder_ids.is_empty() => {
+                Err("bidder allow-list needs at least one bidder".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn build(&self) -> Box<dyn Rule> {
+        match self.clone() {
+            Self::SameBidCurrency { currency } => Box::new(SameBidCurrency {
+                currency_to_follow: currency,
+            }),
+            Self::AllowedCurrencies { currencies } => Box::new(AllowedCurrencies { currencies }),
+            Self::MinimumBidIncrement { min_increment } => {
+                Box::new(MinimumBidIncrement { min_increment })
+            }
+            Self::TieredBidIncrement { tiers } => Box::new(TieredBidIncrement { tiers }),
+            Self::MaximumBid { maximum_bid } => Box::new(MaximumBid { maximum_bid }),
+            Self::BidderAllowList { bidder_ids } => Box::new(BidderAllowList { bidder_ids }),
+            Self::SellerCannotBid { seller_id } => Box::new(SellerCannotBid { seller_id }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawBiddingRulesConfig {
+    rules: Vec<RuleSpec>,
+}
+
+// Rules picked by the organizer for one auction. Stored alongside the auction and validated
+// whenever it is deserialized, so a rule chain can always be rebuilt from it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawBiddingRulesConfig")]
+pub struct BiddingRulesConfig {
+    rules: Vec<RuleSpec>,
+}
+impl TryFrom<RawBiddingRulesConfig> for BiddingRulesConfig {
+    type Error = String;
+
+    fn try_from(raw: RawBiddingRulesConfig) -> Result<Self, Self::Error> {
+        Self::new(raw.rules)
+    }
+}
+impl BiddingRulesConfig {
+    pub fn new(rules: Vec<RuleSpec>) -> Result<Self, String> {
+        for (i, rule) in rules.iter().enumerate() {
+            rule.validate()?;
+            if rules[..i].iter().any(|other| other.code() == rule.code()) {
+                return Err(format!("{} rule is configured more than once", rule.code()));
+            }
+        }
+        let increment_rules = rules
+            .iter()
+            .filter(|rule| {
+                matches!(
+                    rule,
+                    RuleSpec::MinimumBidIncrement { .. } | RuleSpec::TieredBidIncrement { .. }
+                )
+            })
+            .count();
+        if increment_rules > 1 {
+            return Err("only one of the bid increment rules can be configured".into());
+        }
+        Ok(Self { rules })
+    }
+
+    // Rules every ascending auction had before they became configurable
+    pub fn default_for(currency: Currency, min_increment: u32) -> Self {
+        Self {
+            rules: vec![
+                RuleSpec::SameBidCurrency { currency },
+                RuleSpec::MinimumBidIncrement { min_increment },
+            ],
+        }
+    }
+
+    pub fn with_rule(self, rule: RuleSpec) -> Result<Self, String> {
+        let mut rules = self.rules;
+        rules.push(rule);
+        Self::new(rules)
+    }
+
+    // Keeps bids in the given currency unless a currency rule is configured already. Comes
+    // ahead of the other rules, which compare prices without looking at their currency.
+    pub fn with_bid_currency(self, currency: Currency) -> Self {
+        if self
+            .rules
+            .iter()
+            .any(|rule| matches!(rule.code(), SAME_BID_CURRENCY | ALLOWED_CURRENCIES))
+        {
+            return self;
+        }
+        let mut rules = vec![RuleSpec::SameBidCurrency { currency }];
+        rules.extend(self.rules);
+        Self { rules }
+    }
+
+    // Keeps the seller from bidding unless a rule for it is configured already. Comes first so a
+    // shill bid is flagged whatever else is wrong with it.
+    pub fn with_seller_cannot_bid(self, seller_id: UserId) -> Self {
+        if self
+            .rules
+            .iter()
+            .any(|rule| rule.code() == SELLER_CANNOT_BID)
+        {
+            return self;
+        }
+        let mut rules = vec![RuleSpec::SellerCannotBid { seller_id }];
+        rules.extend(self.rules);
+        Self { rules }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, String> {
+        toml::from_str(toml).map_err(|e| e.to_string())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Failed to serialize bidding rules")
+    }
+
+    pub fn get_rules(&self) -> &[RuleSpec] {
+        &self.rules
+    }
+
+    // Minimum increment over the given price, 1 if no increment rule is configured
+    pub fn minimum_increment_at(&self, price: u32) -> u32 {
+        self.rules
+            .iter()
+            .find_map(|rule| match rule {
+                RuleSpec::MinimumBidIncrement { min_increment } => Some(*min_increment),
+                RuleSpec::TieredBidIncrement { tiers } => Some(increment_for_price(tiers, price)),
+                _ => None,
+            })
+            .unwrap_or(1)
+    }
+
+    pub fn build(&self) -> Vec<Box<dyn Rule>> {
+        self.rules.iter().map(|rule| rule.build()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::user::generate_user;
+
+    use super::*;
+
+    #[test]
+    fn minimum_bid_increment_rule() {
+        let user = generate_user();
+        let rule = MinimumBidIncrement { min_increment: 10 };
+        let mut bid = Bid::new(user.get_id(), Price::new(Currency::SGD, 10));
+        bid.set_increment(10);
+        assert!(rule.enforce(&bid).is_ok());
+
+        // Should return err if increment is less than min_increment
+        bid.set_increment(8);
+        let rejection = rule.enforce(&bid).unwrap_err();
+        assert_eq!(rejection.code(), rule.code());
+        assert_eq!(
+            rejection.next_valid_bid(),
+            Some(Price::new(Currency::SGD, 12))
+        );
+    }
+
+    #[test]
+    fn rules_config_loads_from_json_and_toml() {
+        let json = r#"{
+            "rules": [
+                { "rule": "allowed_currencies", "currencies": ["SGD", "MYR"] },
+                { "rule": "minimum_bid_increment", "min_increment": 5 },
+                { "rule": "maximum_bid", "maximum_bid": 500 }
+            ]
+        }"#;
+        let toml = r#"
+            [[rules]]
+            rule = "allowed_currencies"
+            currencies = ["SGD", "MYR"]
+
+            [[rules]]
+            rule = "minimum_bid_increment"
+            min_increment = 5
+
+            [[rules]]
+            rule = "maximum_bid"
+            maximum_bid = 500
+        "#;
+
+        let from_json = BiddingRulesConfig::from_json(json).unwrap();
+        let from_toml = BiddingRulesConfig::from_toml(toml).unwrap();
+        assert_eq!(from_json, from_toml);
+        assert_eq!(from_json.minimum_increment_at(100), 5);
+        assert_eq!(
+            BiddingRulesConfig::from_json(&from_json.to_json()).unwrap(),
+            from_json
+        );
+    }
+
+    #[test]
+    fn invalid_rules_config_is_rejected_on_load() {
+        let zero_increment =
+            r#"{ "rules": [{ "rule": "minimum_bid_increment", "min_increment": 0 }] }"#;
+        assert!(BiddingRulesConfig::from_json(zero_increment).is_err());
+
+        let duplicated = r#"{ "rules": [
+            { "rule": "maximum_bid", "maximum_bid": 100 },
+            { "rule": "maximum_bid", "maximum_bid": 200 }
+        ] }"#;
+        assert!(BiddingRulesConfig::from_json(duplicated).is_err());
+    }
+
+    #[test]
+    fn bidder_allow_list_rule() {
+        let alice = generate_user();
+        let mallory = generate_user();
+        let rules = BiddingRulesConfig::new(vec![RuleSpec::BidderAllowList {
+            bidder_ids: vec![alice.get_id()],
+        }])
+        .unwrap()
+        .build();
+
+        let allowed_bid = Bid::new(alice.get_id(), Price::new(Currency::SGD, 10));
+        assert!(rules[0].enforce(&allowed_bid).is_ok());
+        let blocked_bid = Bid::new(mallory.get_id(), Price::new(Currency::SGD, 10));
+        assert_eq!(
+            rules[0].enforce(&blocked_bid),
+            Err(BidRejection::BidderNotAllowed)
+        );
+    }
+
+    #[test]
+    fn tiered_bid_increment_depends_on_current_price() {
+        let config = BiddingRulesConfig::from_json(
+            r#"{ "rules": [{ "rule": "tiered_bid_increment", "tiers": [
+                { "from": 0, "increment": 1 },
+                { "from": 50, "increment": 5 },
+                { "from": 200, "increment": 10 }
+            ] }] }"#,
+        )
+        .unwrap();
+        assert_eq!(config.minimum_increment_at(5), 1);
+        assert_eq!(config.minimum_increment_at(50), 5);
+        assert_eq!(config.minimum_increment_at(400), 10);
+
+        let user = generate_user();
+        let rules = config.build();
+        let mut bid = Bid::new(user.get_id(), Price::new(Currency::SGD, 63));
+        bid.set_increment(3);
+        assert_eq!(
+            rules[0].enforce(&bid),
+            Err(BidRejection::IncrementTooSmall {
+                minimum_increment: 5,
+                next_minimum_bid: Price::new(Currency::SGD, 65)
+            })
+        );
+    }
+
+    #[test]
+    fn unsorted_increment_tiers_are_rejected() {
+        let tiers = vec![
+            IncrementTier {
+                from: 0,
+                increment: 1,
+            },
+            IncrementTier {
+                from: 200,
+                increment: 10,
+            },
+            IncrementTier {
+                from: 50,
+                increment: 5,
+            },
+        ];
+        assert!(BiddingRulesConfig::new(vec![RuleSpec::TieredBidIncrement { tiers }]).is_err());
+    }
+}
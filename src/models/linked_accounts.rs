@@ -0,0 +1,223 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::user::{User, UserId};
+
+// Contact details used to tell whether two accounts belong to the same person
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountIdentity {
+    pub email: String,
+    pub phone_number: String,
+}
+impl AccountIdentity {
+    pub fn of(user: &User) -> Self {
+        Self {
+            email: user.get_email().to_string(),
+            phone_number: user.get_phone_number(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AccountLink {
+    SameAccount,
+    SamePhoneNumber,
+    // Same mailbox behind a domain alias or a `+tag`, e.g. jane+bg@gmail.com and
+    // jane@googlemail.com
+    EmailAlias,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinkedAccount {
+    pub user_id: UserId,
+    pub link: AccountLink,
+}
+
+// Decides which accounts count as linked, and so as possible shill bidders for one another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkedAccountRegistry {
+    pub match_phone_number: bool,
+    // Groups of email domains delivering to the same mailboxes
+    pub email_domain_aliases: Vec<Vec<String>>,
+}
+impl Default for LinkedAccountRegistry {
+    fn default() -> Self {
+        Self {
+            match_phone_number: true,
+            email_domain_aliases: vec![vec!["gmail.com".into(), "googlemail.com".into()]],
+        }
+    }
+}
+impl LinkedAccountRegistry {
+    pub fn link_between(
+        &self,
+        account: &AccountIdentity,
+        other: &AccountIdentity,
+    ) -> Option<AccountLink> {
+        if account.email.eq_ignore_ascii_case(&other.email) {
+            return Some(AccountLink::SameAccount);
+        }
+        if self.match_phone_number
+            && normalize_phone_number(&account.phone_number)
+                == normalize_phone_number(&other.phone_number)
+        {
+            return Some(AccountLink::SamePhoneNumber);
+        }
+        if self.canonical_email(&account.email) == self.canonical_email(&other.email) {
+            return Some(AccountLink::EmailAlias);
+        }
+        None
+    }
+
+    // Accounts among `users` linked to the given one, other than the account itself
+    pub fn linked_accounts(&self, account: &User, users: &[User]) -> Vec<LinkedAccount> {
+        let identity = AccountIdentity::of(account);
+        users
+            .iter()
+            .filter(|user| user.get_id() != account.get_id())
+            .filter_map(|user| {
+                self.link_between(&identity, &AccountIdentity::of(user))
+                    .map(|link| LinkedAccount {
+                        user_id: user.get_id(),
+                        link,
+                    })
+            })
+            .collect()
+    }
+
+    fn canonical_email(&self, email: &str) -> String {
+        let email = email.to_lowercase();
+        let Some((local_part, domain)) = email.split_once('@') else {
+            return email;
+        };
+        let local_part = local_part.split('+').next().unwrap_or(local_part);
+        let domain = self
+            .email_domain_aliases
+            .iter()
+            .find(|aliases| {
+                aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(domain))
+            })
+            .and_then(|aliases| aliases.first())
+            .map_or(domain.to_string(), |canonical| canonical.to_lowercase());
+        format!("{}@{}", local_part, domain)
+    }
+}
+
+// Links between accounts as they stand when asked, so an account linked to a seller after their
+// auction was created is kept from bidding too. Links are never stored with the auction.
+pub trait AccountLinks: Send + Sync {
+    fn link_between(&self, user_id: UserId, other_id: UserId) -> Option<AccountLink>;
+}
+
+// Identities of the accounts known so far, matched through the registry on every lookup
+#[derive(Debug, Default)]
+pub struct RegisteredAccounts {
+    registry: LinkedAccountRegistry,
+    identities: RwLock<HashMap<UserId, AccountIdentity>>,
+}
+impl RegisteredAccounts {
+    pub fn new(registry: LinkedAccountRegistry) -> Self {
+        Self {
+            registry,
+            identities: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Adds the account, or updates its contact details
+    pub fn register(&self, user: &User) {
+        self.identities
+            .write()
+            .unwrap()
+            .insert(user.get_id(), AccountIdentity::of(user));
+    }
+}
+impl AccountLinks for RegisteredAccounts {
+    fn link_between(&self, user_id: UserId, other_id: UserId) -> Option<AccountLink> {
+        let identities = self.identities.read().unwrap();
+        self.registry
+            .link_between(identities.get(&user_id)?, identities.get(&other_id)?)
+    }
+}
+
+fn normalize_phone_number(phone_number: &str) -> String {
+    phone_number
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::user::generate_user_with_contact;
+
+    use super::*;
+
+    fn identity(email: &str, phone_number: &str) -> AccountIdentity {
+        AccountIdentity {
+            email: email.into(),
+            phone_number: phone_number.into(),
+        }
+    }
+
+    #[test]
+    fn accounts_linked_by_phone_number_or_email_alias() {
+        let registry = LinkedAccountRegistry::default();
+        let seller = identity("jane@gmail.com", "+65 1234 5678");
+
+        assert_eq!(
+            registry.link_between(&seller, &identity("JANE@gmail.com", "+65 8765 4321")),
+            Some(AccountLink::SameAccount)
+        );
+        assert_eq!(
+            registry.link_between(&seller, &identity("shill@gmail.com", "+6512345678")),
+            Some(AccountLink::SamePhoneNumber)
+        );
+        assert_eq!(
+            registry.link_between(
+                &seller,
+                &identity("jane+bg@googlemail.com", "+65 8765 4321")
+            ),
+            Some(AccountLink::EmailAlias)
+        );
+        assert_eq!(
+            registry.link_between(&seller, &identity("john@gmail.com", "+65 8765 4321")),
+            None
+        );
+    }
+
+    #[test]
+    fn registered_accounts_are_linked_once_known() {
+        let accounts = RegisteredAccounts::default();
+        let seller = generate_user_with_contact("jane@gmail.com", "1111 1111");
+        let shill = generate_user_with_contact("jane+bg@googlemail.com", "2222 2222");
+        accounts.register(&seller);
+        assert_eq!(accounts.link_between(seller.get_id(), shill.get_id()), None);
+
+        accounts.register(&shill);
+        assert_eq!(
+            accounts.link_between(seller.get_id(), shill.get_id()),
+            Some(AccountLink::EmailAlias)
+        );
+    }
+
+    #[test]
+    fn linked_accounts_found_among_users() {
+        let registry = LinkedAccountRegistry::default();
+        let seller = generate_user_with_contact("jane@gmail.com", "1111 1111");
+        let users = vec![
+            generate_user_with_contact("jane.shill@gmail.com", "1111 1111"),
+            generate_user_with_contact("john@gmail.com", "2222 2222"),
+        ];
+
+        assert_eq!(
+            registry.linked_accounts(&seller, &users),
+            vec![LinkedAccount {
+                user_id: users[0].get_id(),
+                link: AccountLink::SamePhoneNumber
+            }]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{auction_aggregate::AuctionId, bid::Bid, price::Price, user::UserId};
+
+pub type BidCommitment = [u8; 32];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SealedBidSettlement {
+    // Winner pays their own bid
+    FirstPrice,
+    // Vickrey, winner pays the second highest bid
+    SecondPrice,
+}
+
+// Bids are sealed until the auction's deadline, then revealed for `reveal_window` before the
+// auction is settled
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SealedBidTerms {
+    pub settlement: SealedBidSettlement,
+    #[serde(with = "super::auction::duration_seconds")]
+    pub reveal_window: Duration,
+}
+
+// Shortest nonce accepted on reveal. Prices are small integers, a short nonce would let anyone
+// holding the commitment try every price until the hash matches.
+pub const MIN_NONCE_LENGTH: usize = 16;
+
+// Bidders hash their bid with a secret nonce and submit only the hash while bidding is open, so
+// nobody, including the server, knows the amounts until they are revealed after close. The hash
+// is bound to the auction and the bidder, so it cannot be replayed by someone else.
+pub fn commit(
+    auction_id: AuctionId,
+    bidder_id: UserId,
+    price: Price,
+    nonce: &str,
+) -> BidCommitment {
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "{}:{}:{:?}:{}:{}",
+        auction_id, bidder_id, price.currency, price.value, nonce
+    ));
+    hasher.finalize().into()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SealedBid {
+    pub bidder_id: UserId,
+    pub commitment: BidCommitment,
+    pub revealed_price: Option<Price>,
+}
+
+// Ranks the revealed bids and picks the winner, with the price they pay. Bids that were never
+// revealed are forfeited, as are revealed bids in the wrong currency or below the opening bid.
+pub fn settle(
+    bids: &[SealedBid],
+    opening_bid_price: Price,
+    settlement: SealedBidSettlement,
+) -> Option<Bid> {
+    let mut valid_bids: Vec<(UserId, Price)> = bids
+        .iter()
+        .filter_map(|bid| bid.revealed_price.map(|price| (bid.bidder_id, price)))
+        .filter(|(_, price)| {
+            price.currency == opening_bid_price.currency && *price >= opening_bid_price
+        })
+        .collect();
+    // Stable sort, earliest submission takes the tie
+    valid_bids.sort_by_key(|(_, price)| Reverse(price.value));
+
+    let (winner, highest_price) = valid_bids.first().copied()?;
+    let price = match settlement {
+        SealedBidSettlement::FirstPrice => highest_price,
+        SealedBidSettlement::SecondPrice => valid_bids
+            .get(1)
+            .map_or(opening_bid_price, |(_, price)| *price),
+    };
+    Some(Bid::new(winner, price))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::{
+        models::{
+            auction::{AuctionOutcome, AuctionStatus},
+            auction_aggregate::{AuctionAggregate, AuctionCommand, AuctionTerms},
+            auction_error::AuctionError,
+            clock::{Clock, ManualClock},
+            user::generate_user,
+        },
+        test_support::sgd,
+    };
+
+    use super::*;
+
+    const ALICE_NONCE: &str = "alice-4f1c9a7e2b";
+    const BOB_NONCE: &str = "bob-8d2e6b0c5a13";
+    const BOB_REVISED_NONCE: &str = "bob-1a7f3c9e4d20";
+
+    // Sealed bid auction taking bids for an hour, then revealing them for another
+    fn sealed_bid_auction(
+        settlement: SealedBidSettlement,
+        clock: &ManualClock,
+    ) -> AuctionAggregate {
+        let mut auction_aggregate =
+            AuctionAggregate::new(vec![]).with_clock(Box::new(clock.clone()));
+        auction_aggregate
+            .execute(AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms: AuctionTerms {
+                    opening_bid_price: Some(sgd(10)),
+                    ends_at: Some(clock.now() + Duration::hours(1)),
+                    sealed_bid: Some(SealedBidTerms {
+                        settlement,
+                        reveal_window: Duration::hours(1),
+                    }),
+                    ..AuctionTerms::default()
+                },
+            })
+            .unwrap();
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        auction_aggregate
+    }
+
+    fn submit(
+        auction_aggregate: &mut AuctionAggregate,
+        bidder_id: UserId,
+        price: Price,
+        nonce: &str,
+    ) {
+        auction_aggregate
+            .execute(AuctionCommand::SubmitSealedBid {
+                bidder_id,
+                commitment: commit(1, bidder_id, price, nonce),
+            })
+            .unwrap();
+    }
+
+    fn reveal(
+        auction_aggregate: &mut AuctionAggregate,
+        bidder_id: UserId,
+        price: Price,
+        nonce: &str,
+    ) -> Result<(), AuctionError> {
+        auction_aggregate
+            .execute(AuctionCommand::RevealSealedBid {
+                bidder_id,
+                price,
+                nonce: nonce.to_string(),
+            })
+            .map(|_| ())
+    }
+
+    // Closes bidding at the deadline, then settles once the reveal window is over
+    fn end(auction_aggregate: &mut AuctionAggregate, clock: &ManualClock) {
+        clock.advance(Duration::hours(1));
+        auction_aggregate
+            .execute(AuctionCommand::EndAuction)
+            .unwrap();
+    }
+
+    #[test]
+    fn first_price_winner_pays_own_bid() {
+        let (alice, bob) = (generate_user(), generate_user());
+        let clock = ManualClock::new(Utc::now());
+        let mut auction_aggregate = sealed_bid_auction(SealedBidSettlement::FirstPrice, &clock);
+
+        submit(&mut auction_aggregate, alice.get_id(), sgd(80), ALICE_NONCE);
+        submit(&mut auction_aggregate, bob.get_id(), sgd(60), BOB_NONCE);
+        end(&mut auction_aggregate, &clock);
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Revealing)
+        );
+        reveal(&mut auction_aggregate, alice.get_id(), sgd(80), ALICE_NONCE).unwrap();
+        reveal(&mut auction_aggregate, bob.get_id(), sgd(60), BOB_NONCE).unwrap();
+        end(&mut auction_aggregate, &clock);
+
+        let state = auction_aggregate.get_state();
+        assert_eq!(state.leading_bid().unwrap().get_bidder_id(), alice.get_id());
+        assert_eq!(
+            state.get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(80) })
+        );
+    }
+
+    #[test]
+    fn second_price_winner_pays_runner_up_bid() {
+        let (alice, bob, carol) = (generate_user(), generate_user(), generate_user());
+        let clock = ManualClock::new(Utc::now());
+        let mut auction_aggregate = sealed_bid_auction(SealedBidSettlement::SecondPrice, &clock);
+
+        submit(&mut auction_aggregate, alice.get_id(), sgd(80), ALICE_NONCE);
+        // Revised bid replaces the earlier one
+        submit(&mut auction_aggregate, bob.get_id(), sgd(30), BOB_NONCE);
+        submit(
+            &mut auction_aggregate,
+            bob.get_id(),
+            sgd(60),
+            BOB_REVISED_NONCE,
+        );
+        // Copying someone else's commitment would let a bidder mirror their bid on reveal
+        let copied = commit(1, alice.get_id(), sgd(80), ALICE_NONCE);
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::SubmitSealedBid {
+                bidder_id: carol.get_id(),
+                commitment: copied,
+            }),
+            Err(AuctionError::CommitmentTaken)
+        );
+        // Nor could the copy be revealed as theirs, the bidder is part of the hash
+        assert_ne!(commit(1, carol.get_id(), sgd(80), ALICE_NONCE), copied);
+        // Bids are sealed, open bids are turned down
+        assert_eq!(
+            auction_aggregate.execute(AuctionCommand::MakeBidOffer {
+                bid: Bid::new(carol.get_id(), sgd(90)),
+            }),
+            Err(AuctionError::SealedBidsOnly)
+        );
+        end(&mut auction_aggregate, &clock);
+        reveal(&mut auction_aggregate, alice.get_id(), sgd(80), ALICE_NONCE).unwrap();
+        assert_eq!(
+            reveal(&mut auction_aggregate, bob.get_id(), sgd(30), BOB_NONCE),
+            Err(AuctionError::CommitmentMismatch)
+        );
+        reveal(
+            &mut auction_aggregate,
+            bob.get_id(),
+            sgd(60),
+            BOB_REVISED_NONCE,
+        )
+        .unwrap();
+        end(&mut auction_aggregate, &clock);
+
+        let state = auction_aggregate.get_state();
+        assert_eq!(state.leading_bid().unwrap().get_bidder_id(), alice.get_id());
+        assert_eq!(
+            state.get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(60) })
+        );
+    }
+
+    #[test]
+    fn unrevealed_bid_is_forfeited() {
+        let (alice, bob, carol) = (generate_user(), generate_user(), generate_user());
+        let clock = ManualClock::new(Utc::now());
+        let mut auction_aggregate = sealed_bid_auction(SealedBidSettlement::SecondPrice, &clock);
+
+        submit(&mut auction_aggregate, alice.get_id(), sgd(80), ALICE_NONCE);
+        submit(&mut auction_aggregate, bob.get_id(), sgd(60), BOB_NONCE);
+        submit(&mut auction_aggregate, carol.get_id(), sgd(90), "c1");
+        // Nothing can be revealed while bidding is open
+        assert!(matches!(
+            reveal(&mut auction_aggregate, bob.get_id(), sgd(60), BOB_NONCE),
+            Err(AuctionError::InvalidStatus { .. })
+        ));
+        end(&mut auction_aggregate, &clock);
+        reveal(&mut auction_aggregate, bob.get_id(), sgd(60), BOB_NONCE).unwrap();
+        // Easily guessed nonce is never accepted, even when it matches
+        assert_eq!(
+            reveal(&mut auction_aggregate, carol.get_id(), sgd(90), "c1"),
+            Err(AuctionError::NonceTooShort {
+                minimum_length: MIN_NONCE_LENGTH
+            })
+        );
+        end(&mut auction_aggregate, &clock);
+        assert_eq!(
+            reveal(&mut auction_aggregate, alice.get_id(), sgd(80), ALICE_NONCE),
+            Err(AuctionError::AlreadyEnded)
+        );
+
+        // Replayed from its events like any other auction
+        let events = auction_aggregate.get_uncommitted_events().to_vec();
+        let state = AuctionAggregate::new(events).get_state().clone();
+        assert_eq!(state.leading_bid().unwrap().get_bidder_id(), bob.get_id());
+        assert_eq!(
+            state.get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(10) })
+        );
+    }
+}
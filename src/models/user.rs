@@ -1,38 +1,72 @@
-#[derive(Debug)]
+use std::sync::atomic::{AtomicU32, Ordering};
+
+pub type UserId = u32;
+
+#[derive(Debug, PartialEq)]
 struct Email {
     value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct PhoneNumber {
     country_code: String,
     number: String,
 }
 
 // First, Middle, Last
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct PersonalName(String, String, String);
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct User {
+    id: UserId,
     personal_name: PersonalName,
     email_address: Email,
     phone_number: PhoneNumber,
 }
 
+impl User {
+    pub fn get_id(&self) -> UserId {
+        self.id
+    }
+
+    pub fn get_email(&self) -> &str {
+        &self.email_address.value
+    }
+
+    pub fn get_phone_number(&self) -> String {
+        format!(
+            "{} {}",
+            self.phone_number.country_code, self.phone_number.number
+        )
+    }
+}
+
+// Every generated user gets their own id, clear of the small ids picked by hand
+static NEXT_GENERATED_USER_ID: AtomicU32 = AtomicU32::new(1000);
+
 pub fn generate_user() -> User {
+    generate_user_with_email("junneng@gmail.com")
+}
+
+pub fn generate_user_with_email(email: &str) -> User {
+    generate_user_with_contact(email, "1234 5678")
+}
+
+pub fn generate_user_with_contact(email: &str, phone_number: &str) -> User {
     User {
+        id: NEXT_GENERATED_USER_ID.fetch_add(1, Ordering::Relaxed),
         personal_name: PersonalName(
             String::from("Jun Neng"),
             String::from(""),
             String::from("Mok"),
         ),
         email_address: Email {
-            value: String::from("junneng@gmail.com"),
+            value: String::from(email),
         },
         phone_number: PhoneNumber {
             country_code: String::from("+65"),
-            number: String::from("1234 5678"),
+            number: String::from(phone_number),
         },
     }
 }
@@ -1,2 +1,12 @@
 pub mod auction;
+pub mod auction_read_model;
+pub mod event_store;
+pub mod event_upcaster;
+pub mod in_memory_auction_read_model;
+pub mod in_memory_event_store;
+pub mod in_memory_moderation_record_store;
 pub mod message;
+pub mod moderation_record_store;
+pub mod postgres_auction_read_model;
+pub mod postgres_event_store;
+pub mod postgres_moderation_record_store;
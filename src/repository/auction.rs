@@ -1,22 +1,245 @@
-use crate::{
-    models::auction_aggregate::{AuctionAggregate, AuctionId},
-    prisma::PrismaClient,
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::models::{
+    auction_aggregate::{AuctionAggregate, AuctionEvent, AuctionId, EventId},
+    auction_history::{AuctionStateAt, AuctionStateDiff, PointInTime},
 };
 
-// Event-sourced model
+use super::event_store::{EventStore, EventStoreError};
+
+// Events appended between two snapshots of the same auction
+const DEFAULT_SNAPSHOT_INTERVAL: EventId = 100;
+
+// Event-sourced model, every auction is a stream of events in the event store along with its
+// latest snapshot
 pub struct AuctionRepository {
-    db_client: PrismaClient,
+    event_store: Arc<dyn EventStore>,
+    snapshot_interval: EventId,
 }
 impl AuctionRepository {
-    pub fn new(db_client: PrismaClient) -> Self {
-        Self { db_client }
+    pub fn new(event_store: Arc<dyn EventStore>) -> Self {
+        Self {
+            event_store,
+            snapshot_interval: DEFAULT_SNAPSHOT_INTERVAL,
+        }
+    }
+
+    pub fn with_snapshot_interval(mut self, snapshot_interval: EventId) -> Self {
+        self.snapshot_interval = snapshot_interval.max(1);
+        self
+    }
+
+    pub fn get_event_store(&self) -> Arc<dyn EventStore> {
+        self.event_store.clone()
     }
 
-    pub fn load_events(&self, auction_id: AuctionId) {
-        // Get events from event store
+    // Events of the auction in the order they were raised
+    pub async fn load_events(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        self.event_store.load_events_after(auction_id, 0).await
     }
 
-    pub fn commit_changes(&self, auction_aggregate: AuctionAggregate) {
-        // Persist changes
+    // Events of every auction raised while handling the same originating request
+    pub async fn load_events_by_correlation_id(
+        &self,
+        correlation_id: Uuid,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        self.event_store
+            .load_events_by_correlation_id(correlation_id)
+            .await
+    }
+
+    // Rehydrates the auction from its latest snapshot and the events after it
+    pub async fn load(&self, auction_id: AuctionId) -> Result<AuctionAggregate, EventStoreError> {
+        match self.event_store.load_snapshot(auction_id).await? {
+            Some(snapshot) => {
+                let events = self
+                    .event_store
+                    .load_events_after(auction_id, snapshot.get_version())
+                    .await?;
+                Ok(AuctionAggregate::from_snapshot(snapshot, events))
+            }
+            None => Ok(AuctionAggregate::new(self.load_events(auction_id).await?)),
+        }
+    }
+
+    // Rehydrates the auction as it was at the point in time, from its events alone
+    pub async fn load_as_of(
+        &self,
+        auction_id: AuctionId,
+        point: PointInTime,
+    ) -> Result<AuctionAggregate, EventStoreError> {
+        Ok(AuctionAggregate::as_of(
+            self.load_events(auction_id).await?,
+            point,
+        ))
+    }
+
+    // Leading bid, bidders and status of the auction at the point in time
+    pub async fn load_state_at(
+        &self,
+        auction_id: AuctionId,
+        point: PointInTime,
+    ) -> Result<AuctionStateAt, EventStoreError> {
+        Ok(AuctionStateAt::new(
+            &self.load_as_of(auction_id, point).await?,
+        ))
+    }
+
+    // What changed in the auction between two points in time, always from the earlier point to
+    // the later one whichever order they are given in
+    pub async fn diff_between(
+        &self,
+        auction_id: AuctionId,
+        from: PointInTime,
+        to: PointInTime,
+    ) -> Result<AuctionStateDiff, EventStoreError> {
+        let events = self.load_events(auction_id).await?;
+        let (from, to) = if from.resolve(&events) > to.resolve(&events) {
+            (to, from)
+        } else {
+            (from, to)
+        };
+        let from_state = AuctionStateAt::new(&AuctionAggregate::as_of(events.clone(), from));
+        let to_state = AuctionStateAt::new(&AuctionAggregate::as_of(events.clone(), to));
+        let events_between = events
+            .into_iter()
+            .filter(|event| {
+                event.get_event_id() > from_state.get_version()
+                    && event.get_event_id() <= to_state.get_version()
+            })
+            .collect();
+        Ok(AuctionStateDiff::new(from_state, to_state, events_between))
+    }
+
+    // Every auction with events in the store, to rebuild in-memory schedules after a restart
+    pub async fn load_auction_ids(&self) -> Result<Vec<AuctionId>, EventStoreError> {
+        self.event_store.load_auction_ids().await
+    }
+
+    // Appends the events raised since the aggregate was loaded, all or nothing. Fails with a
+    // concurrency conflict when the stream has moved past `expected_version` in the meantime.
+    pub async fn commit_changes(
+        &self,
+        auction_aggregate: &mut AuctionAggregate,
+        expected_version: EventId,
+    ) -> Result<(), EventStoreError> {
+        let events = auction_aggregate.get_uncommitted_events();
+        if events.is_empty() {
+            return Ok(());
+        }
+        // Snapshot once the append crosses the next multiple of the interval
+        let version = auction_aggregate.get_version();
+        let snapshot = (version / self.snapshot_interval
+            > expected_version / self.snapshot_interval)
+            .then(|| auction_aggregate.take_snapshot());
+        self.event_store
+            .append(
+                auction_aggregate.get_state().get_id(),
+                expected_version,
+                events,
+                snapshot,
+            )
+            .await?;
+        auction_aggregate.mark_committed();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            auction::AuctionStatus,
+            auction_aggregate::{AuctionCommand, AuctionTerms},
+            bid::Bid,
+            price::{Currency, Price},
+        },
+        repository::in_memory_event_store::InMemoryEventStore,
+    };
+
+    use super::*;
+
+    async fn execute(
+        auction_repo: &AuctionRepository,
+        auction_aggregate: &mut AuctionAggregate,
+        command: AuctionCommand,
+    ) {
+        auction_aggregate.execute(command).unwrap();
+        let expected_version = auction_aggregate.get_committed_version();
+        auction_repo
+            .commit_changes(auction_aggregate, expected_version)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn loads_from_snapshot_and_later_events() {
+        let event_store = Arc::new(InMemoryEventStore::new());
+        let auction_repo = AuctionRepository::new(event_store.clone()).with_snapshot_interval(3);
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        let terms = AuctionTerms {
+            opening_bid_price: Some(Price::new(Currency::SGD, 10)),
+            ..AuctionTerms::default()
+        };
+        let create = AuctionCommand::CreateAuction {
+            auction_id: 1,
+            seller_id: 1,
+            terms,
+        };
+        execute(&auction_repo, &mut auction_aggregate, create).await;
+        execute(
+            &auction_repo,
+            &mut auction_aggregate,
+            AuctionCommand::StartAuction,
+        )
+        .await;
+        // Bid and the auction getting under way take it past version 3
+        let bid = Bid::new(2, Price::new(Currency::SGD, 10));
+        let offer = AuctionCommand::MakeBidOffer { bid };
+        execute(&auction_repo, &mut auction_aggregate, offer).await;
+        let close = AuctionCommand::CloseAuction {
+            closed_by: 1,
+            reason: String::from("Sold elsewhere"),
+        };
+        execute(&auction_repo, &mut auction_aggregate, close).await;
+
+        let snapshot = event_store.load_snapshot(1).await.unwrap().unwrap();
+        assert_eq!(snapshot.get_version(), 4);
+        let loaded = auction_repo.load(1).await.unwrap();
+        assert_eq!(loaded.get_committed_version(), 5);
+        assert_eq!(loaded.get_state().get_status(), Some(AuctionStatus::Closed));
+        assert_eq!(
+            loaded.get_state().bids(),
+            auction_aggregate.get_state().bids()
+        );
+
+        // Looking back ignores the snapshot taken past the point
+        let state_at = auction_repo
+            .load_state_at(1, PointInTime::Version(2))
+            .await
+            .unwrap();
+        assert_eq!(state_at.get_status(), Some(AuctionStatus::Started));
+        assert_eq!(state_at.get_leading_bid(), None);
+        let diff = auction_repo
+            .diff_between(1, PointInTime::Version(2), PointInTime::Version(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            diff.status_change(),
+            Some((Some(AuctionStatus::Started), Some(AuctionStatus::Closed)))
+        );
+        assert_eq!(diff.new_bidders(), vec![2]);
+        assert_eq!(diff.get_events().len(), 3);
+        // Points given the other way round make the same diff
+        let swapped = auction_repo
+            .diff_between(1, PointInTime::Version(5), PointInTime::Version(2))
+            .await
+            .unwrap();
+        assert_eq!(swapped, diff);
     }
 }
@@ -0,0 +1,66 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use prisma_client_rust::QueryError;
+
+use crate::models::{
+    auction_aggregate::AuctionId,
+    auction_read_model::{AuctionSummary, BidHistoryEntry},
+};
+
+#[derive(Debug)]
+pub enum ReadModelError {
+    Query(QueryError),
+    // Stored row no longer matches the shape of the read model
+    Serialization(serde_json::Error),
+}
+impl From<QueryError> for ReadModelError {
+    fn from(error: QueryError) -> Self {
+        Self::Query(error)
+    }
+}
+impl From<serde_json::Error> for ReadModelError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serialization(error)
+    }
+}
+impl fmt::Display for ReadModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query(error) => write!(f, "read model query failed: {}", error),
+            Self::Serialization(error) => write!(f, "stored read model is malformed: {}", error),
+        }
+    }
+}
+impl std::error::Error for ReadModelError {}
+
+// Storage of the auction read models, written by the projector and read by queries
+#[async_trait]
+pub trait AuctionReadModel: Send + Sync {
+    async fn get_summary(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionSummary>, ReadModelError>;
+
+    async fn save_summary(&self, summary: &AuctionSummary) -> Result<(), ReadModelError>;
+
+    // Auctions still open for bids with a deadline before `ends_before`, soonest first
+    async fn list_open_auctions_ending_before(
+        &self,
+        ends_before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<AuctionSummary>, ReadModelError>;
+
+    // Recording the same bid twice keeps a single entry
+    async fn add_bid(&self, entry: &BidHistoryEntry) -> Result<(), ReadModelError>;
+
+    // Bids of the auction in the order they were placed
+    async fn get_bid_history(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<BidHistoryEntry>, ReadModelError>;
+
+    // Drops every summary and bid, ahead of a rebuild
+    async fn clear(&self) -> Result<(), ReadModelError>;
+}
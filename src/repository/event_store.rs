@@ -0,0 +1,155 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use prisma_client_rust::QueryError;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::models::auction_aggregate::{AuctionEvent, AuctionId, AuctionSnapshot, EventId};
+
+use super::event_upcaster::UpcastError;
+
+#[derive(Debug)]
+pub enum EventStoreError {
+    Query(QueryError),
+    // Other events were appended to the stream since it was loaded
+    ConcurrencyConflict {
+        auction_id: AuctionId,
+        expected_version: EventId,
+        actual_version: EventId,
+    },
+    // Stored payload no longer matches the shape of `AuctionEvent`
+    Serialization(serde_json::Error),
+    Upcast(UpcastError),
+}
+impl From<QueryError> for EventStoreError {
+    fn from(error: QueryError) -> Self {
+        Self::Query(error)
+    }
+}
+impl From<serde_json::Error> for EventStoreError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serialization(error)
+    }
+}
+impl From<UpcastError> for EventStoreError {
+    fn from(error: UpcastError) -> Self {
+        Self::Upcast(error)
+    }
+}
+impl fmt::Display for EventStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query(error) => write!(f, "event store query failed: {}", error),
+            Self::ConcurrencyConflict {
+                auction_id,
+                expected_version,
+                actual_version,
+            } => write!(
+                f,
+                "auction {} is at version {}, expected version {}",
+                auction_id, actual_version, expected_version
+            ),
+            Self::Serialization(error) => write!(f, "stored event is malformed: {}", error),
+            Self::Upcast(error) => write!(f, "stored event cannot be upcast: {}", error),
+        }
+    }
+}
+impl std::error::Error for EventStoreError {}
+
+// Position of an event across the streams of all auctions, in the order they were appended
+pub type EventPosition = u64;
+
+// Event along with its position in the store, as read by subscriptions
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    position: EventPosition,
+    event: AuctionEvent,
+}
+impl RecordedEvent {
+    pub fn new(position: EventPosition, event: AuctionEvent) -> Self {
+        Self { position, event }
+    }
+
+    pub fn get_position(&self) -> EventPosition {
+        self.position
+    }
+
+    pub fn get_event(&self) -> &AuctionEvent {
+        &self.event
+    }
+
+    pub fn into_event(self) -> AuctionEvent {
+        self.event
+    }
+}
+
+// Append-only storage of auction event streams, one stream per auction, along with the latest
+// snapshot of each auction's state
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    // Events of the auction after `version`, in the order they were raised
+    async fn load_events_after(
+        &self,
+        auction_id: AuctionId,
+        version: EventId,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError>;
+
+    // Events of every auction raised while handling the same originating request, in the order
+    // they were stored
+    async fn load_events_by_correlation_id(
+        &self,
+        correlation_id: Uuid,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError>;
+
+    // Latest snapshot of the auction, none when it was taken with an older shape of the state
+    async fn load_snapshot(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionSnapshot>, EventStoreError>;
+
+    // Every auction with events in the store
+    async fn load_auction_ids(&self) -> Result<Vec<AuctionId>, EventStoreError>;
+
+    // Appends the events to the auction's stream, all or nothing, along with the snapshot if
+    // any. Fails with a concurrency conflict when the stream is not at `expected_version`.
+    async fn append(
+        &self,
+        auction_id: AuctionId,
+        expected_version: EventId,
+        events: &[AuctionEvent],
+        snapshot: Option<AuctionSnapshot>,
+    ) -> Result<(), EventStoreError>;
+
+    // Up to `limit` events of every auction after `position`, in the order they were appended.
+    // Events are only ever appended after the ones already visible, so reading on from the last
+    // position seen never skips any.
+    async fn load_all_events_after(
+        &self,
+        position: EventPosition,
+        limit: usize,
+    ) -> Result<Vec<RecordedEvent>, EventStoreError>;
+
+    // Position the named subscriber has handled events up to, 0 when it has not started yet
+    async fn load_checkpoint(&self, subscriber: &str) -> Result<EventPosition, EventStoreError>;
+
+    async fn save_checkpoint(
+        &self,
+        subscriber: &str,
+        position: EventPosition,
+    ) -> Result<(), EventStoreError>;
+
+    // Up to `limit` appended events not yet published by the outbox relay, in the order they
+    // were appended. Events go into the outbox in the same transaction as their append, as
+    // their public version only, since subscribers include clients.
+    async fn load_unpublished_events(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError>;
+
+    async fn mark_published(&self, event_ids: &[Uuid]) -> Result<(), EventStoreError>;
+
+    // Changes whenever events are appended through this store. Appends made by other processes
+    // are not seen, subscribers poll for those.
+    fn watch_appends(&self) -> watch::Receiver<()>;
+}
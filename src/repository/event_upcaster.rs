@@ -0,0 +1,160 @@
+use std::{fmt, fmt::Debug};
+
+use serde_json::Value;
+
+use crate::models::auction_aggregate::EVENT_SCHEMA_VERSION;
+
+// Rewrites a stored event payload from one schema version to the next. Payloads keep the schema
+// version they were written with, upcasters bring them to the current shape while loading.
+pub trait Upcaster: Debug + Send + Sync {
+    // Schema version of the payloads it accepts, they come out one version later
+    fn source_version(&self) -> u32;
+    fn upcast(&self, payload: Value) -> Value;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpcastError {
+    // No upcaster registered to move payloads on from this version
+    MissingUpcaster { from_version: u32 },
+    // Written by a newer build than this one
+    UnknownSchemaVersion { schema_version: u32 },
+}
+impl fmt::Display for UpcastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingUpcaster { from_version } => {
+                write!(f, "no upcaster for event schema version {}", from_version)
+            }
+            Self::UnknownSchemaVersion { schema_version } => {
+                write!(f, "event schema version {} is unknown", schema_version)
+            }
+        }
+    }
+}
+impl std::error::Error for UpcastError {}
+
+#[derive(Debug, Default)]
+pub struct EventUpcasters {
+    upcasters: Vec<Box<dyn Upcaster>>,
+}
+impl EventUpcasters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, upcaster: Box<dyn Upcaster>) -> Self {
+        self.upcasters
+            .retain(|registered| registered.source_version() != upcaster.source_version());
+        self.upcasters.push(upcaster);
+        self
+    }
+
+    // Brings a payload written with `schema_version` to the current schema version
+    pub fn upcast(&self, payload: Value, schema_version: u32) -> Result<Value, UpcastError> {
+        self.upcast_to(payload, schema_version, EVENT_SCHEMA_VERSION)
+    }
+
+    fn upcast_to(
+        &self,
+        mut payload: Value,
+        schema_version: u32,
+        target_version: u32,
+    ) -> Result<Value, UpcastError> {
+        if schema_version > target_version {
+            return Err(UpcastError::UnknownSchemaVersion { schema_version });
+        }
+        for from_version in schema_version..target_version {
+            let upcaster = self
+                .upcasters
+                .iter()
+                .find(|upcaster| upcaster.source_version() == from_version)
+                .ok_or(UpcastError::MissingUpcaster { from_version })?;
+            payload = upcaster.upcast(payload);
+        }
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // Version 2 renamed `price` to `asking_price`
+    #[derive(Debug)]
+    struct RenameAskingPrice;
+    impl Upcaster for RenameAskingPrice {
+        fn source_version(&self) -> u32 {
+            1
+        }
+
+        fn upcast(&self, mut payload: Value) -> Value {
+            if let Some(fields) = payload.as_object_mut() {
+                if let Some(price) = fields.remove("price") {
+                    fields.insert(String::from("asking_price"), price);
+                }
+            }
+            payload
+        }
+    }
+
+    // Version 3 gave every event a reason
+    #[derive(Debug)]
+    struct DefaultReason;
+    impl Upcaster for DefaultReason {
+        fn source_version(&self) -> u32 {
+            2
+        }
+
+        fn upcast(&self, mut payload: Value) -> Value {
+            if let Some(fields) = payload.as_object_mut() {
+                fields
+                    .entry("reason")
+                    .or_insert(Value::String(String::new()));
+            }
+            payload
+        }
+    }
+
+    fn upcasters() -> EventUpcasters {
+        EventUpcasters::new()
+            .with(Box::new(DefaultReason))
+            .with(Box::new(RenameAskingPrice))
+    }
+
+    #[test]
+    fn upcasts_through_every_version_in_order() {
+        let payload = json!({ "event_type": "PriceDropped", "price": 10 });
+        assert_eq!(
+            upcasters().upcast_to(payload, 1, 3),
+            Ok(json!({ "event_type": "PriceDropped", "asking_price": 10, "reason": "" }))
+        );
+    }
+
+    #[test]
+    fn current_payloads_are_left_alone() {
+        let payload = json!({ "event_type": "AuctionStarted" });
+        assert_eq!(
+            upcasters().upcast_to(payload.clone(), 3, 3),
+            Ok(payload.clone())
+        );
+        assert_eq!(
+            EventUpcasters::new().upcast(payload.clone(), EVENT_SCHEMA_VERSION),
+            Ok(payload)
+        );
+    }
+
+    #[test]
+    fn fails_on_gaps_and_unknown_versions() {
+        let upcasters = EventUpcasters::new().with(Box::new(DefaultReason));
+        assert_eq!(
+            upcasters.upcast_to(json!({}), 1, 3),
+            Err(UpcastError::MissingUpcaster { from_version: 1 })
+        );
+        assert_eq!(
+            upcasters.upcast_to(json!({}), 4, 3),
+            Err(UpcastError::UnknownSchemaVersion { schema_version: 4 })
+        );
+    }
+}
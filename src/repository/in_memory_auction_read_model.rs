@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::models::{
+    auction_aggregate::{AuctionId, EventId},
+    auction_read_model::{AuctionSummary, BidHistoryEntry},
+};
+
+use super::auction_read_model::{AuctionReadModel, ReadModelError};
+
+#[derive(Debug, Default)]
+struct StoredReadModels {
+    summaries: BTreeMap<AuctionId, AuctionSummary>,
+    bids: BTreeMap<(AuctionId, EventId), BidHistoryEntry>,
+}
+
+// Read models kept in memory, meant for tests and local runs
+#[derive(Debug, Default)]
+pub struct InMemoryAuctionReadModel {
+    read_models: Mutex<StoredReadModels>,
+}
+impl InMemoryAuctionReadModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AuctionReadModel for InMemoryAuctionReadModel {
+    async fn get_summary(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionSummary>, ReadModelError> {
+        Ok(self
+            .read_models
+            .lock()
+            .unwrap()
+            .summaries
+            .get(&auction_id)
+            .cloned())
+    }
+
+    async fn save_summary(&self, summary: &AuctionSummary) -> Result<(), ReadModelError> {
+        self.read_models
+            .lock()
+            .unwrap()
+            .summaries
+            .insert(summary.get_auction_id(), summary.clone());
+        Ok(())
+    }
+
+    async fn list_open_auctions_ending_before(
+        &self,
+        ends_before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<AuctionSummary>, ReadModelError> {
+        let mut summaries: Vec<AuctionSummary> = self
+            .read_models
+            .lock()
+            .unwrap()
+            .summaries
+            .values()
+            .filter(|summary| summary.get_status().is_open_for_bids())
+            .filter(|summary| {
+                summary
+                    .get_ends_at()
+                    .is_some_and(|ends_at| ends_at < ends_before)
+            })
+            .cloned()
+            .collect();
+        summaries.sort_by_key(|summary| (summary.get_ends_at(), summary.get_auction_id()));
+        summaries.truncate(limit);
+        Ok(summaries)
+    }
+
+    async fn add_bid(&self, entry: &BidHistoryEntry) -> Result<(), ReadModelError> {
+        self.read_models
+            .lock()
+            .unwrap()
+            .bids
+            .entry((entry.get_auction_id(), entry.get_version()))
+            .or_insert_with(|| entry.clone());
+        Ok(())
+    }
+
+    async fn get_bid_history(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<BidHistoryEntry>, ReadModelError> {
+        Ok(self
+            .read_models
+            .lock()
+            .unwrap()
+            .bids
+            .range((auction_id, 0)..=(auction_id, EventId::MAX))
+            .map(|(_, entry)| entry.clone())
+            .collect())
+    }
+
+    async fn clear(&self) -> Result<(), ReadModelError> {
+        let mut read_models = self.read_models.lock().unwrap();
+        read_models.summaries.clear();
+        read_models.bids.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::models::auction_aggregate::{AuctionEvent, AuctionId, AuctionSnapshot, EventId};
+
+use super::event_store::{EventPosition, EventStore, EventStoreError, RecordedEvent};
+
+#[derive(Debug, Default)]
+struct StoredStreams {
+    // Events of all auctions in the order they were appended
+    events: Vec<AuctionEvent>,
+    snapshots: HashMap<AuctionId, AuctionSnapshot>,
+    checkpoints: HashMap<String, EventPosition>,
+    // Appended events the outbox relay has not published yet
+    outbox: VecDeque<AuctionEvent>,
+}
+impl StoredStreams {
+    fn version_of(&self, auction_id: AuctionId) -> EventId {
+        self.events
+            .iter()
+            .filter(|event| event.get_auction_id() == auction_id)
+            .map(|event| event.get_event_id())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+// Event store kept in memory, with the same ordering and concurrency guarantees as the Postgres
+// one. Nothing survives a restart, meant for tests and local runs.
+#[derive(Debug)]
+pub struct InMemoryEventStore {
+    streams: Mutex<StoredStreams>,
+    appended: watch::Sender<()>,
+}
+impl Default for InMemoryEventStore {
+    fn default() -> Self {
+        Self {
+            streams: Mutex::new(StoredStreams::default()),
+            appended: watch::channel(()).0,
+        }
+    }
+}
+impl InMemoryEventStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.streams.lock().unwrap().events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl EventStore for InMemoryEventStore {
+    async fn load_events_after(
+        &self,
+        auction_id: AuctionId,
+        version: EventId,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        Ok(self
+            .streams
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .filter(|event| event.get_auction_id() == auction_id && event.get_event_id() > version)
+            .cloned()
+            .collect())
+    }
+
+    async fn load_events_by_correlation_id(
+        &self,
+        correlation_id: Uuid,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        Ok(self
+            .streams
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .filter(|event| event.get_metadata().get_correlation_id() == correlation_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn load_snapshot(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionSnapshot>, EventStoreError> {
+        Ok(self
+            .streams
+            .lock()
+            .unwrap()
+            .snapshots
+            .get(&auction_id)
+            .filter(|snapshot| snapshot.is_current())
+            .cloned())
+    }
+
+    async fn load_auction_ids(&self) -> Result<Vec<AuctionId>, EventStoreError> {
+        let auction_ids: BTreeSet<AuctionId> = self
+            .streams
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .map(|event| event.get_auction_id())
+            .collect();
+        Ok(auction_ids.into_iter().collect())
+    }
+
+    async fn append(
+        &self,
+        auction_id: AuctionId,
+        expected_version: EventId,
+        events: &[AuctionEvent],
+        snapshot: Option<AuctionSnapshot>,
+    ) -> Result<(), EventStoreError> {
+        let mut streams = self.streams.lock().unwrap();
+        let actual_version = streams.version_of(auction_id);
+        if actual_version != expected_version {
+            return Err(EventStoreError::ConcurrencyConflict {
+                auction_id,
+                expected_version,
+                actual_version,
+            });
+        }
+        // Same as the unique stream and version constraint of the Postgres store
+        for (version, event) in (expected_version + 1..).zip(events) {
+            if event.get_auction_id() != auction_id || event.get_event_id() != version {
+                return Err(EventStoreError::ConcurrencyConflict {
+                    auction_id,
+                    expected_version: version,
+                    actual_version: event.get_event_id(),
+                });
+            }
+        }
+        streams.events.extend_from_slice(events);
+        streams
+            .outbox
+            .extend(events.iter().filter_map(AuctionEvent::to_public));
+        if let Some(snapshot) = snapshot {
+            streams.snapshots.insert(auction_id, snapshot);
+        }
+        drop(streams);
+        self.appended.send_replace(());
+        Ok(())
+    }
+
+    // Positions are 1-based indexes into the events of all auctions
+    async fn load_all_events_after(
+        &self,
+        position: EventPosition,
+        limit: usize,
+    ) -> Result<Vec<RecordedEvent>, EventStoreError> {
+        Ok(self
+            .streams
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .enumerate()
+            .skip(position as usize)
+            .take(limit)
+            .map(|(index, event)| RecordedEvent::new(index as EventPosition + 1, event.clone()))
+            .collect())
+    }
+
+    async fn load_checkpoint(&self, subscriber: &str) -> Result<EventPosition, EventStoreError> {
+        Ok(self
+            .streams
+            .lock()
+            .unwrap()
+            .checkpoints
+            .get(subscriber)
+            .copied()
+            .unwrap_or(0))
+    }
+
+    async fn save_checkpoint(
+        &self,
+        subscriber: &str,
+        position: EventPosition,
+    ) -> Result<(), EventStoreError> {
+        self.streams
+            .lock()
+            .unwrap()
+            .checkpoints
+            .insert(subscriber.to_string(), position);
+        Ok(())
+    }
+
+    async fn load_unpublished_events(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        Ok(self
+            .streams
+            .lock()
+            .unwrap()
+            .outbox
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_published(&self, event_ids: &[Uuid]) -> Result<(), EventStoreError> {
+        self.streams
+            .lock()
+            .unwrap()
+            .outbox
+            .retain(|event| !event_ids.contains(&event.get_metadata().get_event_id()));
+        Ok(())
+    }
+
+    fn watch_appends(&self) -> watch::Receiver<()> {
+        self.appended.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::auction_aggregate::{
+        AuctionAggregate, AuctionCommand, AuctionTerms, CommandContext,
+    };
+
+    use super::*;
+
+    fn auction_events(auction_id: AuctionId, context: CommandContext) -> Vec<AuctionEvent> {
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        let command = AuctionCommand::CreateAuction {
+            auction_id,
+            seller_id: 1,
+            terms: AuctionTerms::default(),
+        };
+        auction_aggregate
+            .execute_in_context(command, context)
+            .unwrap();
+        auction_aggregate
+            .execute_in_context(AuctionCommand::StartAuction, context)
+            .unwrap();
+        auction_aggregate.get_uncommitted_events().to_vec()
+    }
+
+    #[tokio::test]
+    async fn keeps_streams_apart_and_in_order() {
+        let event_store = InMemoryEventStore::new();
+        let context = CommandContext::new();
+        event_store
+            .append(1, 0, &auction_events(1, context), None)
+            .await
+            .unwrap();
+        event_store
+            .append(2, 0, &auction_events(2, CommandContext::new()), None)
+            .await
+            .unwrap();
+
+        let events = event_store.load_events_after(1, 0).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.get_auction_id() == 1));
+        assert_eq!(events[1].get_event_id(), 2);
+        assert_eq!(event_store.load_events_after(1, 1).await.unwrap().len(), 1);
+        assert_eq!(event_store.load_auction_ids().await.unwrap(), vec![1, 2]);
+        let recorded_events = event_store.load_all_events_after(1, 2).await.unwrap();
+        let positions: Vec<_> = recorded_events
+            .iter()
+            .map(|recorded_event| recorded_event.get_position())
+            .collect();
+        assert_eq!(positions, vec![2, 3]);
+        assert_eq!(recorded_events[1].get_event().get_auction_id(), 2);
+        assert_eq!(
+            event_store
+                .load_events_by_correlation_id(context.get_correlation_id())
+                .await
+                .unwrap(),
+            events
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_appends_to_a_stream_that_moved_on() {
+        let event_store = InMemoryEventStore::new();
+        let events = auction_events(1, CommandContext::new());
+        event_store.append(1, 0, &events, None).await.unwrap();
+
+        assert!(matches!(
+            event_store.append(1, 0, &events, None).await,
+            Err(EventStoreError::ConcurrencyConflict {
+                expected_version: 0,
+                actual_version: 2,
+                ..
+            })
+        ));
+        // Events out of sequence are turned down as well
+        assert!(event_store.append(1, 2, &events, None).await.is_err());
+        assert_eq!(event_store.len(), 2);
+    }
+}
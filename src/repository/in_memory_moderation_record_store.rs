@@ -0,0 +1,40 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::models::{auction::ModerationRecord, auction_aggregate::AuctionId};
+
+use super::moderation_record_store::{ModerationRecordStore, ModerationStoreError};
+
+// Moderation records kept in memory, meant for tests and local runs
+#[derive(Debug, Default)]
+pub struct InMemoryModerationRecordStore {
+    records: Mutex<Vec<ModerationRecord>>,
+}
+impl InMemoryModerationRecordStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ModerationRecordStore for InMemoryModerationRecordStore {
+    async fn add_record(&self, record: &ModerationRecord) -> Result<(), ModerationStoreError> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+
+    async fn get_records(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<ModerationRecord>, ModerationStoreError> {
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|record| record.auction_id == auction_id)
+            .cloned()
+            .collect())
+    }
+}
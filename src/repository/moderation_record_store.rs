@@ -0,0 +1,46 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use prisma_client_rust::QueryError;
+
+use crate::models::{auction::ModerationRecord, auction_aggregate::AuctionId};
+
+#[derive(Debug)]
+pub enum ModerationStoreError {
+    Query(QueryError),
+    // Stored rejection no longer matches the shape of `BidRejection`
+    Serialization(serde_json::Error),
+}
+impl From<QueryError> for ModerationStoreError {
+    fn from(error: QueryError) -> Self {
+        Self::Query(error)
+    }
+}
+impl From<serde_json::Error> for ModerationStoreError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serialization(error)
+    }
+}
+impl fmt::Display for ModerationStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query(error) => write!(f, "moderation record query failed: {}", error),
+            Self::Serialization(error) => {
+                write!(f, "stored moderation record is malformed: {}", error)
+            }
+        }
+    }
+}
+impl std::error::Error for ModerationStoreError {}
+
+// Storage of the bids flagged as possible shill bidding, kept until a moderator looks at them
+#[async_trait]
+pub trait ModerationRecordStore: Send + Sync {
+    async fn add_record(&self, record: &ModerationRecord) -> Result<(), ModerationStoreError>;
+
+    // Records of the auction in the order they were flagged
+    async fn get_records(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<ModerationRecord>, ModerationStoreError>;
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use prisma_client_rust::{raw, PrismaValue};
+use serde::Deserialize;
+
+use crate::{
+    models::{
+        auction_aggregate::AuctionId,
+        auction_read_model::{AuctionSummary, BidHistoryEntry},
+    },
+    prisma::PrismaClient,
+};
+
+use super::auction_read_model::{AuctionReadModel, ReadModelError};
+
+// Rows are built into the JSON shape of the read model by the queries themselves
+#[derive(Deserialize)]
+struct StoredReadModel {
+    read_model: String,
+}
+
+fn decode<T: serde::de::DeserializeOwned>(
+    stored: Vec<StoredReadModel>,
+) -> Result<Vec<T>, ReadModelError> {
+    stored
+        .iter()
+        .map(|stored| Ok(serde_json::from_str(&stored.read_model)?))
+        .collect()
+}
+
+// Read models in the `AuctionSummary` and `AuctionBidHistory` tables
+pub struct PostgresAuctionReadModel {
+    db_client: Arc<PrismaClient>,
+}
+impl PostgresAuctionReadModel {
+    pub fn new(db_client: Arc<PrismaClient>) -> Self {
+        Self { db_client }
+    }
+}
+
+#[async_trait]
+impl AuctionReadModel for PostgresAuctionReadModel {
+    async fn get_summary(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionSummary>, ReadModelError> {
+        let stored: Vec<StoredReadModel> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT json_build_object('auction_id', auction_id, 'seller_id', seller_id,
+                    'status', status,
+                    'current_price', CASE WHEN current_price IS NULL THEN NULL
+                        ELSE json_build_object('currency', currency, 'value', current_price) END,
+                    'bid_count', bid_count,
+                    'ends_at', to_char(ends_at, 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"'),
+                    'version', version)::text AS read_model
+                FROM "AuctionSummary" WHERE auction_id = {}"#,
+                PrismaValue::Int(auction_id as i64)
+            ))
+            .exec()
+            .await?;
+        Ok(decode(stored)?.pop())
+    }
+
+    async fn save_summary(&self, summary: &AuctionSummary) -> Result<(), ReadModelError> {
+        let current_price = summary.get_current_price();
+        self.db_client
+            ._execute_raw(raw!(
+                r#"INSERT INTO "AuctionSummary" (auction_id, seller_id, status, currency,
+                current_price, bid_count, ends_at, version)
+                VALUES ({}, {}, {}, {}, {}, {}, {}::timestamp, {})
+                ON CONFLICT (auction_id) DO UPDATE SET seller_id = EXCLUDED.seller_id,
+                status = EXCLUDED.status, currency = EXCLUDED.currency,
+                current_price = EXCLUDED.current_price, bid_count = EXCLUDED.bid_count,
+                ends_at = EXCLUDED.ends_at, version = EXCLUDED.version,
+                updated_at = CURRENT_TIMESTAMP"#,
+                PrismaValue::Int(summary.get_auction_id() as i64),
+                summary
+                    .get_seller_id()
+                    .map_or(PrismaValue::Null, |seller_id| PrismaValue::Int(
+                        seller_id as i64
+                    )),
+                PrismaValue::String(format!("{:?}", summary.get_status())),
+                current_price.map_or(PrismaValue::Null, |price| PrismaValue::String(format!(
+                    "{:?}",
+                    price.currency
+                ))),
+                current_price.map_or(PrismaValue::Null, |price| PrismaValue::Int(
+                    price.value as i64
+                )),
+                PrismaValue::Int(summary.get_bid_count() as i64),
+                summary
+                    .get_ends_at()
+                    .map_or(PrismaValue::Null, |ends_at| PrismaValue::String(
+                        ends_at.to_rfc3339()
+                    )),
+                PrismaValue::Int(summary.get_version() as i64)
+            ))
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn list_open_auctions_ending_before(
+        &self,
+        ends_before: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<AuctionSummary>, ReadModelError> {
+        let stored: Vec<StoredReadModel> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT json_build_object('auction_id', auction_id, 'seller_id', seller_id,
+                    'status', status,
+                    'current_price', CASE WHEN current_price IS NULL THEN NULL
+                        ELSE json_build_object('currency', currency, 'value', current_price) END,
+                    'bid_count', bid_count,
+                    'ends_at', to_char(ends_at, 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"'),
+                    'version', version)::text AS read_model
+                FROM "AuctionSummary"
+                WHERE status IN ('Started', 'InProgress', 'Ending') AND ends_at < {}::timestamp
+                ORDER BY ends_at, auction_id LIMIT {}"#,
+                PrismaValue::String(ends_before.to_rfc3339()),
+                PrismaValue::Int(limit as i64)
+            ))
+            .exec()
+            .await?;
+        decode(stored)
+    }
+
+    async fn add_bid(&self, entry: &BidHistoryEntry) -> Result<(), ReadModelError> {
+        self.db_client
+            ._execute_raw(raw!(
+                r#"INSERT INTO "AuctionBidHistory" (auction_id, version, bidder_id, currency,
+                price, placed_at)
+                VALUES ({}, {}, {}, {}, {}, {}::timestamp)
+                ON CONFLICT (auction_id, version) DO NOTHING"#,
+                PrismaValue::Int(entry.get_auction_id() as i64),
+                PrismaValue::Int(entry.get_version() as i64),
+                PrismaValue::Int(entry.get_bidder_id() as i64),
+                PrismaValue::String(format!("{:?}", entry.get_price().currency)),
+                PrismaValue::Int(entry.get_price().value as i64),
+                PrismaValue::String(entry.get_placed_at().to_rfc3339())
+            ))
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bid_history(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<BidHistoryEntry>, ReadModelError> {
+        let stored: Vec<StoredReadModel> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT json_build_object('auction_id', auction_id, 'version', version,
+                    'bidder_id', bidder_id,
+                    'price', json_build_object('currency', currency, 'value', price),
+                    'placed_at', to_char(placed_at, 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                    )::text AS read_model
+                FROM "AuctionBidHistory" WHERE auction_id = {} ORDER BY version"#,
+                PrismaValue::Int(auction_id as i64)
+            ))
+            .exec()
+            .await?;
+        decode(stored)
+    }
+
+    async fn clear(&self) -> Result<(), ReadModelError> {
+        self.db_client
+            ._execute_raw(raw!(
+                r#"TRUNCATE TABLE "AuctionSummary", "AuctionBidHistory""#
+            ))
+            .exec()
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,381 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use prisma_client_rust::{raw, PrismaValue, Raw};
+use serde::Deserialize;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::{
+    models::auction_aggregate::{
+        AuctionEvent, AuctionId, AuctionSnapshot, EventId, EVENT_SCHEMA_VERSION, SNAPSHOT_VERSION,
+    },
+    prisma::PrismaClient,
+};
+
+use super::{
+    event_store::{EventPosition, EventStore, EventStoreError, RecordedEvent},
+    event_upcaster::{EventUpcasters, Upcaster},
+};
+
+// Key of the advisory lock every append takes before inserting its events. Holding it until
+// commit keeps the positions of the events in commit order, so readers never see an event while
+// one at an earlier position is still in flight.
+const APPEND_ORDER_LOCK: i64 = -1;
+
+#[derive(Deserialize)]
+struct StoredEvent {
+    position: i64,
+    schema_version: i64,
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct StoredSnapshot {
+    snapshot_version: i64,
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct StoredVersion {
+    version: i64,
+}
+
+#[derive(Deserialize)]
+struct StoredStream {
+    stream_id: i64,
+}
+
+#[derive(Deserialize)]
+struct StoredCheckpoint {
+    position: i64,
+}
+
+// Events in the `AuctionEvent` table, with the latest snapshot of each auction in the
+// `AuctionSnapshot` table, events waiting to be published in the `AuctionOutbox` table and
+// subscriber checkpoints in the `SubscriptionCheckpoint` table
+pub struct PostgresEventStore {
+    db_client: Arc<PrismaClient>,
+    upcasters: EventUpcasters,
+    appended: watch::Sender<()>,
+}
+impl PostgresEventStore {
+    pub fn new(db_client: Arc<PrismaClient>) -> Self {
+        Self {
+            db_client,
+            upcasters: EventUpcasters::new(),
+            appended: watch::channel(()).0,
+        }
+    }
+
+    // Registers the upcaster for events stored before the last change to their schema
+    pub fn with_upcaster(mut self, upcaster: Box<dyn Upcaster>) -> Self {
+        self.upcasters = self.upcasters.with(upcaster);
+        self
+    }
+
+    // Envelope columns are merged back into the payload as its metadata before decoding
+    async fn query_events(&self, query: Raw) -> Result<Vec<RecordedEvent>, EventStoreError> {
+        let stored_events: Vec<StoredEvent> = self.db_client._query_raw(query).exec().await?;
+        stored_events
+            .iter()
+            .map(|stored_event| {
+                let payload = serde_json::from_str(&stored_event.payload)?;
+                let payload = self
+                    .upcasters
+                    .upcast(payload, stored_event.schema_version as u32)?;
+                Ok(RecordedEvent::new(
+                    stored_event.position as EventPosition,
+                    serde_json::from_value(payload)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventStore for PostgresEventStore {
+    async fn load_events_after(
+        &self,
+        auction_id: AuctionId,
+        version: EventId,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        self.query_events(raw!(
+            r#"SELECT id AS position, schema_version::bigint AS schema_version, (payload || jsonb_build_object(
+                'metadata', jsonb_build_object(
+                    'event_id', event_id,
+                    'occurred_at', to_char(occurred_at, 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'),
+                    'actor_id', actor_id,
+                    'correlation_id', correlation_id,
+                    'causation_id', causation_id)))::text AS payload
+            FROM "AuctionEvent" WHERE stream_id = {} AND version > {} ORDER BY version"#,
+            PrismaValue::Int(auction_id as i64),
+            PrismaValue::Int(version as i64)
+        ))
+        .await
+        .map(|recorded_events| {
+            recorded_events
+                .into_iter()
+                .map(RecordedEvent::into_event)
+                .collect()
+        })
+    }
+
+    async fn load_events_by_correlation_id(
+        &self,
+        correlation_id: Uuid,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        self.query_events(raw!(
+            r#"SELECT id AS position, schema_version::bigint AS schema_version, (payload || jsonb_build_object(
+                'metadata', jsonb_build_object(
+                    'event_id', event_id,
+                    'occurred_at', to_char(occurred_at, 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'),
+                    'actor_id', actor_id,
+                    'correlation_id', correlation_id,
+                    'causation_id', causation_id)))::text AS payload
+            FROM "AuctionEvent" WHERE correlation_id = {}::uuid ORDER BY id"#,
+            PrismaValue::String(correlation_id.to_string())
+        ))
+        .await
+        .map(|recorded_events| {
+            recorded_events
+                .into_iter()
+                .map(RecordedEvent::into_event)
+                .collect()
+        })
+    }
+
+    async fn load_snapshot(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Option<AuctionSnapshot>, EventStoreError> {
+        let stored_snapshots: Vec<StoredSnapshot> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT snapshot_version::bigint AS snapshot_version, state::text AS state
+                FROM "AuctionSnapshot" WHERE stream_id = {}"#,
+                PrismaValue::Int(auction_id as i64)
+            ))
+            .exec()
+            .await?;
+        let Some(stored_snapshot) = stored_snapshots.first() else {
+            return Ok(None);
+        };
+        // Taken with an older shape of the state, the auction is replayed from its events instead
+        if stored_snapshot.snapshot_version != SNAPSHOT_VERSION as i64 {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&stored_snapshot.state)?))
+    }
+
+    async fn load_auction_ids(&self) -> Result<Vec<AuctionId>, EventStoreError> {
+        let stored_streams: Vec<StoredStream> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT DISTINCT stream_id::bigint AS stream_id FROM "AuctionEvent"
+                ORDER BY stream_id"#
+            ))
+            .exec()
+            .await?;
+        Ok(stored_streams
+            .iter()
+            .map(|stored_stream| stored_stream.stream_id as AuctionId)
+            .collect())
+    }
+
+    async fn append(
+        &self,
+        auction_id: AuctionId,
+        expected_version: EventId,
+        events: &[AuctionEvent],
+        snapshot: Option<AuctionSnapshot>,
+    ) -> Result<(), EventStoreError> {
+        let mut statements = vec![];
+        for event in events {
+            // Envelope goes into its own columns to be queryable, the payload keeps the rest
+            let metadata = event.get_metadata();
+            statements.push(raw!(
+                r#"INSERT INTO "AuctionEvent" (stream_id, version, event_type, schema_version,
+                payload, event_id, occurred_at, actor_id, correlation_id, causation_id)
+                VALUES ({}, {}, {}, {}, {}::jsonb - 'metadata', {}::uuid, {}::timestamp, {},
+                {}::uuid, {}::uuid)"#,
+                PrismaValue::Int(event.get_auction_id() as i64),
+                PrismaValue::Int(event.get_event_id() as i64),
+                PrismaValue::String(format!("{:?}", event.get_event_type())),
+                PrismaValue::Int(EVENT_SCHEMA_VERSION as i64),
+                PrismaValue::String(serde_json::to_string(event)?),
+                PrismaValue::String(metadata.get_event_id().to_string()),
+                PrismaValue::String(metadata.get_occurred_at().to_rfc3339()),
+                metadata
+                    .get_actor_id()
+                    .map_or(PrismaValue::Null, |actor_id| PrismaValue::Int(
+                        actor_id as i64
+                    )),
+                PrismaValue::String(metadata.get_correlation_id().to_string()),
+                metadata
+                    .get_causation_id()
+                    .map_or(PrismaValue::Null, |causation_id| {
+                        PrismaValue::String(causation_id.to_string())
+                    })
+            ));
+        }
+        // Outbox rows commit or roll back along with the events they publish
+        for event in events.iter().filter_map(AuctionEvent::to_public) {
+            statements.push(raw!(
+                r#"INSERT INTO "AuctionOutbox" (event_id, stream_id, schema_version, payload)
+                VALUES ({}::uuid, {}, {}, {}::jsonb)"#,
+                PrismaValue::String(event.get_metadata().get_event_id().to_string()),
+                PrismaValue::Int(event.get_auction_id() as i64),
+                PrismaValue::Int(EVENT_SCHEMA_VERSION as i64),
+                PrismaValue::String(serde_json::to_string(&event)?)
+            ));
+        }
+        if let Some(snapshot) = snapshot {
+            statements.push(raw!(
+                r#"INSERT INTO "AuctionSnapshot" (stream_id, version, snapshot_version, state)
+                VALUES ({}, {}, {}, {}::jsonb)
+                ON CONFLICT (stream_id) DO UPDATE SET version = EXCLUDED.version,
+                snapshot_version = EXCLUDED.snapshot_version, state = EXCLUDED.state,
+                taken_at = CURRENT_TIMESTAMP"#,
+                PrismaValue::Int(auction_id as i64),
+                PrismaValue::Int(snapshot.get_version() as i64),
+                PrismaValue::Int(snapshot.get_snapshot_version() as i64),
+                PrismaValue::String(serde_json::to_string(&snapshot)?)
+            ));
+        }
+
+        self.db_client
+            ._transaction()
+            .run(|client| async move {
+                // Appends to the same stream wait for each other until the end of the transaction
+                client
+                    ._execute_raw(raw!(
+                        "SELECT pg_advisory_xact_lock({})",
+                        PrismaValue::Int(auction_id as i64)
+                    ))
+                    .exec()
+                    .await?;
+                let stored_versions: Vec<StoredVersion> = client
+                    ._query_raw(raw!(
+                        r#"SELECT COALESCE(MAX(version), 0)::bigint AS version
+                        FROM "AuctionEvent" WHERE stream_id = {}"#,
+                        PrismaValue::Int(auction_id as i64)
+                    ))
+                    .exec()
+                    .await?;
+                let actual_version = stored_versions
+                    .first()
+                    .map_or(0, |stored_version| stored_version.version as EventId);
+                if actual_version != expected_version {
+                    return Err(EventStoreError::ConcurrencyConflict {
+                        auction_id,
+                        expected_version,
+                        actual_version,
+                    });
+                }
+                client
+                    ._execute_raw(raw!(
+                        "SELECT pg_advisory_xact_lock({})",
+                        PrismaValue::Int(APPEND_ORDER_LOCK)
+                    ))
+                    .exec()
+                    .await?;
+                for statement in statements {
+                    client._execute_raw(statement).exec().await?;
+                }
+                Ok(())
+            })
+            .await?;
+        self.appended.send_replace(());
+        Ok(())
+    }
+
+    async fn load_all_events_after(
+        &self,
+        position: EventPosition,
+        limit: usize,
+    ) -> Result<Vec<RecordedEvent>, EventStoreError> {
+        self.query_events(raw!(
+            r#"SELECT id AS position, schema_version::bigint AS schema_version, (payload || jsonb_build_object(
+                'metadata', jsonb_build_object(
+                    'event_id', event_id,
+                    'occurred_at', to_char(occurred_at, 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"'),
+                    'actor_id', actor_id,
+                    'correlation_id', correlation_id,
+                    'causation_id', causation_id)))::text AS payload
+            FROM "AuctionEvent" WHERE id > {} ORDER BY id LIMIT {}"#,
+            PrismaValue::Int(position as i64),
+            PrismaValue::Int(limit as i64)
+        ))
+        .await
+    }
+
+    async fn load_checkpoint(&self, subscriber: &str) -> Result<EventPosition, EventStoreError> {
+        let stored_checkpoints: Vec<StoredCheckpoint> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT position FROM "SubscriptionCheckpoint" WHERE subscriber = {}"#,
+                PrismaValue::String(subscriber.to_string())
+            ))
+            .exec()
+            .await?;
+        Ok(stored_checkpoints.first().map_or(0, |stored_checkpoint| {
+            stored_checkpoint.position as EventPosition
+        }))
+    }
+
+    async fn save_checkpoint(
+        &self,
+        subscriber: &str,
+        position: EventPosition,
+    ) -> Result<(), EventStoreError> {
+        self.db_client
+            ._execute_raw(raw!(
+                r#"INSERT INTO "SubscriptionCheckpoint" (subscriber, position) VALUES ({}, {})
+                ON CONFLICT (subscriber) DO UPDATE SET position = EXCLUDED.position,
+                updated_at = CURRENT_TIMESTAMP"#,
+                PrismaValue::String(subscriber.to_string()),
+                PrismaValue::Int(position as i64)
+            ))
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn load_unpublished_events(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<AuctionEvent>, EventStoreError> {
+        self.query_events(raw!(
+            r#"SELECT id AS position, schema_version::bigint AS schema_version,
+                payload::text AS payload
+            FROM "AuctionOutbox" WHERE published_at IS NULL ORDER BY id LIMIT {}"#,
+            PrismaValue::Int(limit as i64)
+        ))
+        .await
+        .map(|recorded_events| {
+            recorded_events
+                .into_iter()
+                .map(RecordedEvent::into_event)
+                .collect()
+        })
+    }
+
+    async fn mark_published(&self, event_ids: &[Uuid]) -> Result<(), EventStoreError> {
+        for event_id in event_ids {
+            self.db_client
+                ._execute_raw(raw!(
+                    r#"UPDATE "AuctionOutbox" SET published_at = CURRENT_TIMESTAMP
+                    WHERE event_id = {}::uuid"#,
+                    PrismaValue::String(event_id.to_string())
+                ))
+                .exec()
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn watch_appends(&self) -> watch::Receiver<()> {
+        self.appended.subscribe()
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use prisma_client_rust::{raw, PrismaValue};
+use serde::Deserialize;
+
+use crate::{
+    models::{auction::ModerationRecord, auction_aggregate::AuctionId},
+    prisma::PrismaClient,
+};
+
+use super::moderation_record_store::{ModerationRecordStore, ModerationStoreError};
+
+// Rows are built into the JSON shape of the record by the query itself
+#[derive(Deserialize)]
+struct StoredModerationRecord {
+    record: String,
+}
+
+// Moderation records in the `ModerationRecord` table
+pub struct PostgresModerationRecordStore {
+    db_client: Arc<PrismaClient>,
+}
+impl PostgresModerationRecordStore {
+    pub fn new(db_client: Arc<PrismaClient>) -> Self {
+        Self { db_client }
+    }
+}
+
+#[async_trait]
+impl ModerationRecordStore for PostgresModerationRecordStore {
+    async fn add_record(&self, record: &ModerationRecord) -> Result<(), ModerationStoreError> {
+        self.db_client
+            ._execute_raw(raw!(
+                r#"INSERT INTO "ModerationRecord" (auction_id, bidder_id, code, rejection,
+                recorded_at)
+                VALUES ({}, {}, {}, {}::jsonb, {}::timestamp)"#,
+                PrismaValue::Int(record.auction_id as i64),
+                PrismaValue::Int(record.bidder_id as i64),
+                PrismaValue::String(record.rejection.code().to_string()),
+                PrismaValue::String(serde_json::to_string(&record.rejection)?),
+                PrismaValue::String(record.recorded_at.to_rfc3339())
+            ))
+            .exec()
+            .await?;
+        Ok(())
+    }
+
+    async fn get_records(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<ModerationRecord>, ModerationStoreError> {
+        let stored: Vec<StoredModerationRecord> = self
+            .db_client
+            ._query_raw(raw!(
+                r#"SELECT json_build_object('auction_id', auction_id, 'bidder_id', bidder_id,
+                    'rejection', rejection,
+                    'recorded_at', to_char(recorded_at, 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"')
+                    )::text AS record
+                FROM "ModerationRecord" WHERE auction_id = {} ORDER BY id"#,
+                PrismaValue::Int(auction_id as i64)
+            ))
+            .exec()
+            .await?;
+        stored
+            .iter()
+            .map(|stored| Ok(serde_json::from_str(&stored.record)?))
+            .collect()
+    }
+}
@@ -1,5 +1,9 @@
 use std::str::FromStr;
 
+use uuid::Uuid;
+
+use crate::models::auction_aggregate::CommandContext;
+
 #[derive(Debug)]
 pub enum HttpMethod {
     GET,
@@ -46,6 +50,27 @@ impl HttpRequest {
             Some(headers) => headers.set_header(header, val),
         }
     }
+
+    // Context of the commands handling this request leads to, continuing the caller's
+    // correlation when it sent one
+    pub fn command_context(&self) -> CommandContext {
+        let mut context = CommandContext::new();
+        let Some(headers) = &self.headers else {
+            return context;
+        };
+        if let Some(correlation_id) = parse_uuid(&headers.correlation_id) {
+            context = context.with_correlation_id(correlation_id);
+        }
+        if let Some(request_id) = parse_uuid(&headers.request_id) {
+            context = context.with_causation_id(request_id);
+        }
+        context
+    }
+}
+
+fn parse_uuid(val: &Option<String>) -> Option<Uuid> {
+    val.as_deref()
+        .and_then(|val| Uuid::parse_str(val.trim()).ok())
 }
 
 #[derive(Debug)]
@@ -54,6 +79,8 @@ struct HttpHeaders {
     user_agent: Option<String>,
     accept: Option<String>,
     content_type: Option<String>,
+    correlation_id: Option<String>,
+    request_id: Option<String>,
 }
 impl HttpHeaders {
     pub fn empty() -> Self {
@@ -62,6 +89,8 @@ impl HttpHeaders {
             user_agent: None,
             accept: None,
             content_type: None,
+            correlation_id: None,
+            request_id: None,
         }
     }
 
@@ -72,6 +101,8 @@ impl HttpHeaders {
             "User-Agent" => self.user_agent = val,
             "Accept" => self.accept = val,
             "Content-Type" => self.content_type = val,
+            "X-Correlation-Id" => self.correlation_id = val,
+            "X-Request-Id" => self.request_id = val,
             _ => return,
         }
     }
@@ -136,4 +167,18 @@ Content-Length: 28
         parse_request(buffer);
         assert!(true);
     }
+
+    #[test]
+    fn command_context_continues_request_correlation() {
+        let correlation_id = Uuid::new_v4();
+        let request_id = Uuid::new_v4();
+        let mut request = HttpRequest::empty();
+        request.set_header("X-Correlation-Id", &correlation_id.to_string());
+        request.set_header("X-Request-Id", &request_id.to_string());
+
+        let context = request.command_context();
+        assert_eq!(context.get_correlation_id(), correlation_id);
+        assert_eq!(context.get_causation_id(), Some(request_id));
+        assert_eq!(context.get_actor_id(), None);
+    }
 }
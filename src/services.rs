@@ -1,2 +1,7 @@
 pub mod auction_manager;
+pub mod auction_projector;
+pub mod deadline_scheduler;
+pub mod dutch_auction_ticker;
+pub mod event_subscription;
 pub mod messaging;
+pub mod outbox_relay;
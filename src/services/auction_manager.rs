@@ -1,35 +1,428 @@
-use crate::repository::auction::AuctionRepository;
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+
+use crate::{
+    models::{
+        auction::ModerationRecord,
+        auction_aggregate::{
+            AuctionCommand, AuctionEvent, AuctionId, AuctionTerms, CommandContext,
+        },
+        auction_error::AuctionError,
+        bid::Bid,
+        linked_accounts::AccountLinks,
+        price::Price,
+        sealed_auction::BidCommitment,
+        user::UserId,
+    },
+    repository::{
+        auction::AuctionRepository,
+        event_store::EventStoreError,
+        moderation_record_store::{ModerationRecordStore, ModerationStoreError},
+    },
+    services::deadline_scheduler::DeadlineSchedule,
+};
+
+#[derive(Debug)]
+pub enum AuctionServiceError {
+    Rejected(AuctionError),
+    EventStore(EventStoreError),
+}
+impl From<AuctionError> for AuctionServiceError {
+    fn from(error: AuctionError) -> Self {
+        Self::Rejected(error)
+    }
+}
+impl From<EventStoreError> for AuctionServiceError {
+    fn from(error: EventStoreError) -> Self {
+        Self::EventStore(error)
+    }
+}
+impl fmt::Display for AuctionServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejected(error) => write!(f, "{}", error),
+            Self::EventStore(error) => write!(f, "{}", error),
+        }
+    }
+}
+impl std::error::Error for AuctionServiceError {}
+
+// Attempts at a command before giving up on an auction that keeps changing under it
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
 
 pub struct AuctionManagerService {
     auction_repo: AuctionRepository,
+    moderation_records: Arc<dyn ModerationRecordStore>,
+    // Deadline scheduler's schedule, told about every change to an auction's deadline
+    deadline_schedule: Option<Arc<Mutex<DeadlineSchedule>>>,
+    account_links: Option<Arc<dyn AccountLinks>>,
+    max_attempts: u32,
 }
 impl AuctionManagerService {
-    pub fn new(auction_repo: AuctionRepository) -> Self {
-        Self { auction_repo }
+    pub fn new(
+        auction_repo: AuctionRepository,
+        moderation_records: Arc<dyn ModerationRecordStore>,
+    ) -> Self {
+        Self {
+            auction_repo,
+            moderation_records,
+            deadline_schedule: None,
+            account_links: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    // Auctions created or changed from here on are tracked by the scheduler sharing the schedule
+    pub fn with_deadline_schedule(
+        mut self,
+        deadline_schedule: Arc<Mutex<DeadlineSchedule>>,
+    ) -> Self {
+        self.deadline_schedule = Some(deadline_schedule);
+        self
+    }
+
+    // Bids from accounts linked to the seller are turned down as shill bids
+    pub fn with_account_links(mut self, account_links: Arc<dyn AccountLinks>) -> Self {
+        self.account_links = Some(account_links);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub async fn create_auction(
+        &self,
+        auction_id: AuctionId,
+        seller_id: UserId,
+        terms: AuctionTerms,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let command = AuctionCommand::CreateAuction {
+            auction_id,
+            seller_id,
+            terms,
+        };
+        self.execute(auction_id, command, context.with_actor(seller_id))
+            .await
+    }
+
+    pub async fn start_auction(
+        &self,
+        auction_id: AuctionId,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        self.execute(auction_id, AuctionCommand::StartAuction, context)
+            .await
+    }
+
+    pub async fn place_bid_for_auction(
+        &self,
+        auction_id: AuctionId,
+        bid: Bid,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let bidder_id = bid.get_bidder_id();
+        let command = AuctionCommand::MakeBidOffer { bid };
+        self.execute(auction_id, command, context.with_actor(bidder_id))
+            .await
+    }
+
+    // Bid price is the bidder's secret maximum
+    pub async fn place_proxy_bid_for_auction(
+        &self,
+        auction_id: AuctionId,
+        bid: Bid,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let bidder_id = bid.get_bidder_id();
+        let command = AuctionCommand::PlaceProxyBid { bid };
+        self.execute(auction_id, command, context.with_actor(bidder_id))
+            .await
+    }
+
+    pub async fn accept_current_price(
+        &self,
+        auction_id: AuctionId,
+        bidder_id: UserId,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let command = AuctionCommand::AcceptCurrentPrice { bidder_id };
+        self.execute(auction_id, command, context.with_actor(bidder_id))
+            .await
+    }
+
+    // Sealed bid auctions, only the commitment is sent while bidding is open
+    pub async fn submit_sealed_bid(
+        &self,
+        auction_id: AuctionId,
+        bidder_id: UserId,
+        commitment: BidCommitment,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let command = AuctionCommand::SubmitSealedBid {
+            bidder_id,
+            commitment,
+        };
+        self.execute(auction_id, command, context.with_actor(bidder_id))
+            .await
+    }
+
+    pub async fn reveal_sealed_bid(
+        &self,
+        auction_id: AuctionId,
+        bidder_id: UserId,
+        price: Price,
+        nonce: String,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let command = AuctionCommand::RevealSealedBid {
+            bidder_id,
+            price,
+            nonce,
+        };
+        self.execute(auction_id, command, context.with_actor(bidder_id))
+            .await
+    }
+
+    pub async fn get_moderation_records(
+        &self,
+        auction_id: AuctionId,
+    ) -> Result<Vec<ModerationRecord>, ModerationStoreError> {
+        self.moderation_records.get_records(auction_id).await
     }
 
-    pub fn create_auction() {}
+    // Keeps bids that look like shill bidding for moderators, whichever command carried them
+    async fn record_for_moderation(
+        &self,
+        auction_id: AuctionId,
+        command: &AuctionCommand,
+        error: &AuctionError,
+    ) {
+        let (Some(bidder_id), AuctionError::BidRejected(rejection)) =
+            (command.get_bidder_id(), error)
+        else {
+            return;
+        };
+        let Some(record) =
+            ModerationRecord::for_rejection(auction_id, bidder_id, rejection, Utc::now())
+        else {
+            return;
+        };
+        if let Err(error) = self.moderation_records.add_record(&record).await {
+            println!(
+                "Failed to record bid of {} on auction {} for moderation: {}",
+                bidder_id, auction_id, error
+            );
+        }
+    }
 
-    pub fn place_bid_for_auction() {
-        // Get aution aggregate from repository, rehydrate its state from events
-        // Execute PlaceBid command on auction aggregate
-        // Save auction via repository
+    // Runs the command against the latest state of the auction. When another command got its
+    // events in first, the auction is reloaded and the command run again, so it is never accepted
+    // based on stale state.
+    async fn execute(
+        &self,
+        auction_id: AuctionId,
+        command: AuctionCommand,
+        context: CommandContext,
+    ) -> Result<Vec<AuctionEvent>, AuctionServiceError> {
+        let mut attempt = 1;
+        loop {
+            // Get aution aggregate from repository, rehydrate its state from events
+            let mut auction_aggregate = self.auction_repo.load(auction_id).await?;
+            if let Some(account_links) = &self.account_links {
+                auction_aggregate = auction_aggregate.with_account_links(account_links.clone());
+            }
+            // Execute command on auction aggregate
+            let events = match auction_aggregate.execute_in_context(command.clone(), context) {
+                Ok(events) => events,
+                Err(error) => {
+                    self.record_for_moderation(auction_id, &command, &error)
+                        .await;
+                    return Err(error.into());
+                }
+            };
+            // Save auction via repository
+            let expected_version = auction_aggregate.get_committed_version();
+            match self
+                .auction_repo
+                .commit_changes(&mut auction_aggregate, expected_version)
+                .await
+            {
+                Ok(()) => {
+                    if let Some(deadline_schedule) = &self.deadline_schedule {
+                        deadline_schedule
+                            .lock()
+                            .unwrap()
+                            .track(auction_aggregate.get_state());
+                    }
+                    return Ok(events);
+                }
+                Err(EventStoreError::ConcurrencyConflict { .. }) if attempt < self.max_attempts => {
+                    println!(
+                        "Auction {} changed while handling command, retrying ({}/{})",
+                        auction_id, attempt, self.max_attempts
+                    );
+                    attempt += 1;
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{prisma::PrismaClient, repository::auction::AuctionRepository};
+    use chrono::Duration;
+
+    use crate::{
+        models::{
+            auction::{AuctionStatus, PriceDrop},
+            bid_rejection::BidRejection,
+        },
+        test_support::{in_memory_auction_manager, sgd},
+    };
 
     use super::*;
 
+    fn english_auction_terms() -> AuctionTerms {
+        AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            ..AuctionTerms::default()
+        }
+    }
+
     #[tokio::test]
     async fn create_auction() {
-        let db_client = PrismaClient::_builder()
-            .build()
+        let (_, auction_manager_service) = in_memory_auction_manager();
+        let events = auction_manager_service
+            .create_auction(1, 1, english_auction_terms(), CommandContext::new())
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].get_metadata().get_actor_id(), Some(1));
+
+        let auction_aggregate = auction_manager_service.auction_repo.load(1).await.unwrap();
+        assert_eq!(auction_aggregate.get_committed_version(), 1);
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Created)
+        );
+        assert!(matches!(
+            auction_manager_service
+                .create_auction(1, 1, english_auction_terms(), CommandContext::new())
+                .await,
+            Err(AuctionServiceError::Rejected(
+                AuctionError::AlreadyCreated { auction_id: 1 }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn place_bid_for_auction() {
+        let (_, auction_manager_service) = in_memory_auction_manager();
+        auction_manager_service
+            .create_auction(1, 1, english_auction_terms(), CommandContext::new())
+            .await
+            .unwrap();
+        auction_manager_service
+            .start_auction(1, CommandContext::new())
+            .await
+            .unwrap();
+
+        let context = CommandContext::new();
+        let bid = Bid::new(2, sgd(10));
+        let events = auction_manager_service
+            .place_bid_for_auction(1, bid, context)
+            .await
+            .unwrap();
+        let correlated_events = auction_manager_service
+            .auction_repo
+            .load_events_by_correlation_id(context.get_correlation_id())
+            .await
+            .unwrap();
+        assert_eq!(correlated_events, events);
+
+        // Rejected bid leaves the stream as it was
+        let bid = Bid::new(3, sgd(10));
+        assert!(auction_manager_service
+            .place_bid_for_auction(1, bid, CommandContext::new())
+            .await
+            .is_err());
+        let auction_aggregate = auction_manager_service.auction_repo.load(1).await.unwrap();
+        assert_eq!(auction_aggregate.get_committed_version(), 4);
+        assert_eq!(auction_aggregate.get_state().bids().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn records_shill_bids_of_every_kind_for_moderation() {
+        let (_, auction_manager_service) = in_memory_auction_manager();
+        // English auction for open and proxy bids, dutch auction for accepting the price
+        let dutch_auction_terms = AuctionTerms {
+            asking_price: Some(sgd(100)),
+            price_drop: Some(PriceDrop {
+                step: 10,
+                floor_price: 50,
+                interval: Duration::minutes(1),
+            }),
+            ..AuctionTerms::default()
+        };
+        for (auction_id, terms) in [(1, english_auction_terms()), (2, dutch_auction_terms)] {
+            auction_manager_service
+                .create_auction(auction_id, 1, terms, CommandContext::new())
+                .await
+                .unwrap();
+            auction_manager_service
+                .start_auction(auction_id, CommandContext::new())
+                .await
+                .unwrap();
+        }
+
+        let bid = Bid::new(1, sgd(20));
+        for result in [
+            auction_manager_service
+                .place_bid_for_auction(1, bid.clone(), CommandContext::new())
+                .await,
+            auction_manager_service
+                .place_proxy_bid_for_auction(1, bid, CommandContext::new())
+                .await,
+            auction_manager_service
+                .accept_current_price(2, 1, CommandContext::new())
+                .await,
+        ] {
+            assert!(matches!(
+                result,
+                Err(AuctionServiceError::Rejected(AuctionError::BidRejected(
+                    BidRejection::SellerCannotBid
+                )))
+            ));
+        }
+        // Ordinary rejections are not for moderators
+        let bid = Bid::new(2, sgd(5));
+        assert!(auction_manager_service
+            .place_bid_for_auction(1, bid, CommandContext::new())
+            .await
+            .is_err());
+
+        let mut moderation_records = auction_manager_service
+            .get_moderation_records(1)
             .await
-            .expect("Failed to initialize db client");
-        let auction_repo = AuctionRepository::new(db_client);
-        let auction_manager_service = AuctionManagerService::new(auction_repo);
+            .unwrap();
+        assert_eq!(moderation_records.len(), 2);
+        moderation_records.extend(
+            auction_manager_service
+                .get_moderation_records(2)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(moderation_records.len(), 3);
+        assert!(moderation_records.iter().all(
+            |record| record.bidder_id == 1 && record.rejection == BidRejection::SellerCannotBid
+        ));
     }
 }
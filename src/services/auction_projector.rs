@@ -0,0 +1,225 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+
+use crate::{
+    models::{
+        auction_aggregate::{AuctionEvent, AuctionId},
+        auction_read_model::{AuctionSummary, BidHistoryEntry},
+    },
+    repository::{
+        auction_read_model::{AuctionReadModel, ReadModelError},
+        event_store::{EventStore, EventStoreError, RecordedEvent},
+    },
+    services::event_subscription::{EventSubscriber, EventSubscription, SubscriberError},
+};
+
+// Name the projector's checkpoint is kept under
+pub const PROJECTOR_SUBSCRIPTION: &str = "auction_projector";
+
+#[derive(Debug)]
+pub enum ProjectionError {
+    EventStore(EventStoreError),
+    ReadModel(ReadModelError),
+}
+impl From<EventStoreError> for ProjectionError {
+    fn from(error: EventStoreError) -> Self {
+        Self::EventStore(error)
+    }
+}
+impl From<ReadModelError> for ProjectionError {
+    fn from(error: ReadModelError) -> Self {
+        Self::ReadModel(error)
+    }
+}
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventStore(error) => write!(f, "{}", error),
+            Self::ReadModel(error) => write!(f, "{}", error),
+        }
+    }
+}
+impl std::error::Error for ProjectionError {}
+
+// Keeps the auction summaries and bid histories in line with the event store. Each summary
+// remembers the version of its stream it is up to date with, so projecting picks up where it
+// left off and replaying events is harmless.
+pub struct AuctionProjector {
+    event_store: Arc<dyn EventStore>,
+    read_model: Arc<dyn AuctionReadModel>,
+    poll_interval: std::time::Duration,
+}
+impl AuctionProjector {
+    pub fn new(
+        event_store: Arc<dyn EventStore>,
+        read_model: Arc<dyn AuctionReadModel>,
+        poll_interval: std::time::Duration,
+    ) -> Self {
+        Self {
+            event_store,
+            read_model,
+            poll_interval,
+        }
+    }
+
+    pub fn get_read_model(&self) -> Arc<dyn AuctionReadModel> {
+        self.read_model.clone()
+    }
+
+    // Applies the events to the read models, in the order given
+    pub async fn project(&self, events: &[AuctionEvent]) -> Result<(), ProjectionError> {
+        let mut summaries: HashMap<AuctionId, AuctionSummary> = HashMap::new();
+        for event in events {
+            let auction_id = event.get_auction_id();
+            if let Entry::Vacant(vacant) = summaries.entry(auction_id) {
+                let summary = match self.read_model.get_summary(auction_id).await? {
+                    Some(summary) => Some(summary),
+                    None => AuctionSummary::created(event),
+                };
+                let Some(summary) = summary else {
+                    println!(
+                        "Skipping event {} of auction {} with no summary",
+                        event.get_event_id(),
+                        auction_id
+                    );
+                    continue;
+                };
+                vacant.insert(summary);
+            }
+            // Bids go in before the summary's version moves past them
+            if let Some(entry) = BidHistoryEntry::from_event(event) {
+                self.read_model.add_bid(&entry).await?;
+            }
+            if let Some(summary) = summaries.get_mut(&auction_id) {
+                summary.apply(event);
+            }
+        }
+        for summary in summaries.values() {
+            self.read_model.save_summary(summary).await?;
+        }
+        Ok(())
+    }
+
+    // Projects the events stored since each summary was last saved, returns how many
+    pub async fn catch_up(&self) -> Result<usize, ProjectionError> {
+        let mut projected = 0;
+        for auction_id in self.event_store.load_auction_ids().await? {
+            let version = self
+                .read_model
+                .get_summary(auction_id)
+                .await?
+                .map_or(0, |summary| summary.get_version());
+            let events = self
+                .event_store
+                .load_events_after(auction_id, version)
+                .await?;
+            self.project(&events).await?;
+            projected += events.len();
+        }
+        Ok(projected)
+    }
+
+    // Drops the read models and projects every stored event again
+    pub async fn rebuild(&self) -> Result<usize, ProjectionError> {
+        self.read_model.clear().await?;
+        let projected = self.catch_up().await?;
+        println!("Rebuilt auction read models from {} events", projected);
+        Ok(projected)
+    }
+
+    // Follows the event log through a subscription, projecting events as they are appended
+    pub fn spawn(self) -> JoinHandle<()> {
+        let event_store = self.event_store.clone();
+        let poll_interval = self.poll_interval;
+        EventSubscription::new(PROJECTOR_SUBSCRIPTION, event_store, Arc::new(self))
+            .with_poll_interval(poll_interval)
+            .spawn()
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for AuctionProjector {
+    async fn handle(&self, events: &[RecordedEvent]) -> Result<(), SubscriberError> {
+        let events: Vec<AuctionEvent> = events
+            .iter()
+            .map(|recorded_event| recorded_event.get_event().clone())
+            .collect();
+        Ok(self.project(&events).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::{
+        models::{
+            auction::AuctionStatus,
+            auction_aggregate::{AuctionTerms, CommandContext},
+            bid::Bid,
+        },
+        repository::in_memory_auction_read_model::InMemoryAuctionReadModel,
+        test_support::{create_and_start_auction, in_memory_auction_manager, sgd},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn projects_listings_and_bid_history() {
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        let projector = AuctionProjector::new(
+            event_store.clone(),
+            Arc::new(InMemoryAuctionReadModel::new()),
+            std::time::Duration::from_secs(1),
+        );
+        let now = Utc::now();
+        for (auction_id, ends_in) in [(1, 30), (2, 10), (3, 120)] {
+            let terms = AuctionTerms {
+                opening_bid_price: Some(sgd(10)),
+                ends_at: Some(now + Duration::minutes(ends_in)),
+                ..AuctionTerms::default()
+            };
+            create_and_start_auction(&auction_manager_service, auction_id, terms).await;
+        }
+        for (bidder_id, price) in [(2, 10), (3, 15)] {
+            auction_manager_service
+                .place_bid_for_auction(1, Bid::new(bidder_id, sgd(price)), CommandContext::new())
+                .await
+                .unwrap();
+        }
+        assert_eq!(projector.catch_up().await.unwrap(), 9);
+        assert_eq!(projector.catch_up().await.unwrap(), 0);
+
+        let read_model = projector.get_read_model();
+        let summary = read_model.get_summary(1).await.unwrap().unwrap();
+        assert_eq!(summary.get_status(), AuctionStatus::InProgress);
+        assert_eq!(summary.get_current_price(), Some(sgd(15)));
+        assert_eq!(summary.get_bid_count(), 2);
+        let ending_soon = read_model
+            .list_open_auctions_ending_before(now + Duration::hours(1), 10)
+            .await
+            .unwrap();
+        let auction_ids: Vec<AuctionId> = ending_soon
+            .iter()
+            .map(|summary| summary.get_auction_id())
+            .collect();
+        assert_eq!(auction_ids, vec![2, 1]);
+        let bid_history = read_model.get_bid_history(1).await.unwrap();
+        let bidder_ids: Vec<_> = bid_history
+            .iter()
+            .map(|entry| entry.get_bidder_id())
+            .collect();
+        assert_eq!(bidder_ids, vec![2, 3]);
+
+        // Rebuilding from scratch ends up with the same read models
+        assert_eq!(projector.rebuild().await.unwrap(), 9);
+        assert_eq!(read_model.get_summary(1).await.unwrap(), Some(summary));
+        assert_eq!(read_model.get_bid_history(1).await.unwrap(), bid_history);
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use tokio::{task::JoinHandle, time};
+
+use crate::{
+    models::{
+        auction::AuctionStatus,
+        auction_aggregate::{AuctionCommand, AuctionId, AuctionState},
+        clock::{Clock, SystemClock},
+    },
+    repository::auction::AuctionRepository,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct ScheduledDeadline {
+    ends_at: DateTime<Utc>,
+    ending_announced: bool,
+}
+
+// Deadlines of the auctions still open for bids, or revealing sealed bids
+#[derive(Debug, Default)]
+pub struct DeadlineSchedule {
+    deadlines: HashMap<AuctionId, ScheduledDeadline>,
+}
+impl DeadlineSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Keeps the schedule in line with the auction's latest state, picking up extended deadlines
+    // and dropping auctions that are over
+    pub fn track(&mut self, state: &AuctionState) {
+        match (state.get_status(), state.get_ends_at()) {
+            (Some(status), Some(ends_at))
+                if status.is_open_for_bids() || status == AuctionStatus::Revealing =>
+            {
+                // Nothing is announced ahead of the end of a reveal window
+                self.deadlines.insert(
+                    state.get_id(),
+                    ScheduledDeadline {
+                        ends_at,
+                        ending_announced: matches!(
+                            status,
+                            AuctionStatus::Ending | AuctionStatus::Revealing
+                        ),
+                    },
+                );
+            }
+            _ => {
+                self.deadlines.remove(&state.get_id());
+            }
+        }
+    }
+
+    pub fn get_ends_at(&self, auction_id: AuctionId) -> Option<DateTime<Utc>> {
+        self.deadlines
+            .get(&auction_id)
+            .map(|deadline| deadline.ends_at)
+    }
+
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    // Command the auction is due for, if its warning threshold or deadline has passed
+    pub fn due_command(
+        &self,
+        auction_id: AuctionId,
+        now: DateTime<Utc>,
+        warning_threshold: Duration,
+    ) -> Option<AuctionCommand> {
+        let deadline = self.deadlines.get(&auction_id)?;
+        if now >= deadline.ends_at {
+            Some(AuctionCommand::EndAuction)
+        } else if !deadline.ending_announced && now >= deadline.ends_at - warning_threshold {
+            Some(AuctionCommand::AnnounceEnding)
+        } else {
+            None
+        }
+    }
+
+    pub fn due_auction_ids(
+        &self,
+        now: DateTime<Utc>,
+        warning_threshold: Duration,
+    ) -> Vec<AuctionId> {
+        let mut auction_ids: Vec<AuctionId> = self
+            .deadlines
+            .keys()
+            .copied()
+            .filter(|auction_id| {
+                self.due_command(*auction_id, now, warning_threshold)
+                    .is_some()
+            })
+            .collect();
+        auction_ids.sort();
+        auction_ids
+    }
+}
+
+// Ends auctions on time. Announces the approaching deadline once `warning_threshold` is left
+// and ends the auction once the deadline is reached, both through the aggregate so the events
+// are stored like any other.
+pub struct DeadlineScheduler {
+    auction_repo: Arc<AuctionRepository>,
+    schedule: Arc<Mutex<DeadlineSchedule>>,
+    warning_threshold: Duration,
+    poll_interval: std::time::Duration,
+    clock: Arc<dyn Clock>,
+}
+impl DeadlineScheduler {
+    pub fn new(
+        auction_repo: Arc<AuctionRepository>,
+        warning_threshold: Duration,
+        poll_interval: std::time::Duration,
+    ) -> Self {
+        Self {
+            auction_repo,
+            schedule: Arc::new(Mutex::new(DeadlineSchedule::new())),
+            warning_threshold,
+            poll_interval,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // Shared with the auction manager, so auctions created after recovery get tracked too
+    pub fn get_schedule(&self) -> Arc<Mutex<DeadlineSchedule>> {
+        self.schedule.clone()
+    }
+
+    // Rebuilds the schedule from the event store, so no auction is left running after a restart
+    pub async fn recover(&self) {
+        let auction_ids = match self.auction_repo.load_auction_ids().await {
+            Ok(auction_ids) => auction_ids,
+            Err(error) => {
+                println!("Failed to recover auction deadlines: {}", error);
+                return;
+            }
+        };
+        for auction_id in auction_ids {
+            let auction_aggregate = match self.auction_repo.load(auction_id).await {
+                Ok(auction_aggregate) => auction_aggregate,
+                Err(error) => {
+                    println!("Failed to load auction {}: {}", auction_id, error);
+                    continue;
+                }
+            };
+            self.schedule
+                .lock()
+                .unwrap()
+                .track(auction_aggregate.get_state());
+        }
+        println!(
+            "Recovered deadlines of {} auctions",
+            self.schedule.lock().unwrap().len()
+        );
+    }
+
+    pub async fn run_due_commands(&self) {
+        let now = self.clock.now();
+        let due_auction_ids = self
+            .schedule
+            .lock()
+            .unwrap()
+            .due_auction_ids(now, self.warning_threshold);
+        for auction_id in due_auction_ids {
+            let mut auction_aggregate = match self.auction_repo.load(auction_id).await {
+                Ok(auction_aggregate) => auction_aggregate.with_clock(Box::new(self.clock.clone())),
+                Err(error) => {
+                    println!("Failed to load auction {}: {}", auction_id, error);
+                    continue;
+                }
+            };
+            // Deadline may have been extended by a late bid since it was scheduled
+            let command = {
+                let mut schedule = self.schedule.lock().unwrap();
+                schedule.track(auction_aggregate.get_state());
+                schedule.due_command(auction_id, now, self.warning_threshold)
+            };
+            let Some(command) = command else {
+                continue;
+            };
+            match auction_aggregate.execute(command) {
+                Ok(events) => {
+                    for event in events {
+                        println!(
+                            "Auction {} reached {:?}",
+                            auction_id,
+                            event.get_event_type()
+                        );
+                    }
+                }
+                Err(error) => {
+                    println!("Deadline of auction {} not handled: {}", auction_id, error)
+                }
+            }
+            // Left as scheduled when saving fails, to be retried on the next tick
+            let expected_version = auction_aggregate.get_committed_version();
+            match self
+                .auction_repo
+                .commit_changes(&mut auction_aggregate, expected_version)
+                .await
+            {
+                Ok(()) => self
+                    .schedule
+                    .lock()
+                    .unwrap()
+                    .track(auction_aggregate.get_state()),
+                Err(error) => println!("Failed to save auction {}: {}", auction_id, error),
+            }
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            self.recover().await;
+            let mut interval = time::interval(self.poll_interval);
+            loop {
+                interval.tick().await;
+                self.run_due_commands().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            auction::AuctionOutcome,
+            auction_aggregate::{AuctionAggregate, AuctionTerms, CommandContext},
+            clock::ManualClock,
+            sealed_auction::{commit, SealedBidSettlement, SealedBidTerms},
+        },
+        repository::in_memory_event_store::InMemoryEventStore,
+        test_support::{create_and_start_auction, in_memory_auction_manager, sgd},
+    };
+
+    use super::*;
+
+    fn auction_terms(ends_at: DateTime<Utc>) -> AuctionTerms {
+        AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            ends_at: Some(ends_at),
+            ..AuctionTerms::default()
+        }
+    }
+
+    fn started_auction(ends_at: DateTime<Utc>) -> AuctionAggregate {
+        let mut auction_aggregate = AuctionAggregate::new(vec![]);
+        auction_aggregate
+            .execute(AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms: auction_terms(ends_at),
+            })
+            .unwrap();
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        auction_aggregate
+    }
+
+    #[test]
+    fn schedule_announces_ending_then_ends_auction() {
+        let now = Utc::now();
+        let ends_at = now + Duration::minutes(30);
+        let warning_threshold = Duration::minutes(5);
+        let mut auction_aggregate = started_auction(ends_at);
+        let mut schedule = DeadlineSchedule::new();
+        schedule.track(auction_aggregate.get_state());
+
+        assert!(schedule.due_auction_ids(now, warning_threshold).is_empty());
+        let warned_at = now + Duration::minutes(25);
+        assert!(matches!(
+            schedule.due_command(1, warned_at, warning_threshold),
+            Some(AuctionCommand::AnnounceEnding)
+        ));
+
+        auction_aggregate
+            .execute(AuctionCommand::AnnounceEnding)
+            .unwrap();
+        schedule.track(auction_aggregate.get_state());
+        assert!(schedule
+            .due_command(1, warned_at, warning_threshold)
+            .is_none());
+        assert!(matches!(
+            schedule.due_command(1, ends_at, warning_threshold),
+            Some(AuctionCommand::EndAuction)
+        ));
+    }
+
+    #[test]
+    fn schedule_drops_auctions_that_are_over() {
+        let ends_at = Utc::now() + Duration::minutes(30);
+        let mut auction_aggregate = started_auction(ends_at);
+        let mut schedule = DeadlineSchedule::new();
+        schedule.track(auction_aggregate.get_state());
+        assert_eq!(schedule.get_ends_at(1), Some(ends_at));
+
+        auction_aggregate
+            .execute(AuctionCommand::CloseAuction {
+                closed_by: 1,
+                reason: String::from("Sold elsewhere"),
+            })
+            .unwrap();
+        schedule.track(auction_aggregate.get_state());
+        assert!(schedule.is_empty());
+    }
+
+    #[tokio::test]
+    async fn scheduler_ends_recovered_auctions_on_time() {
+        let clock = ManualClock::new(Utc::now());
+        let ends_at = clock.now() + Duration::minutes(30);
+        let auction_repo = Arc::new(AuctionRepository::new(Arc::new(InMemoryEventStore::new())));
+        let mut auction_aggregate = started_auction(ends_at);
+        auction_repo
+            .commit_changes(&mut auction_aggregate, 0)
+            .await
+            .unwrap();
+        let scheduler = DeadlineScheduler::new(
+            auction_repo.clone(),
+            Duration::minutes(5),
+            std::time::Duration::from_secs(1),
+        )
+        .with_clock(Arc::new(clock.clone()));
+        scheduler.recover().await;
+        assert_eq!(scheduler.get_schedule().lock().unwrap().len(), 1);
+
+        clock.advance(Duration::minutes(26));
+        scheduler.run_due_commands().await;
+        let auction_aggregate = auction_repo.load(1).await.unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Ending)
+        );
+
+        clock.advance(Duration::minutes(4));
+        scheduler.run_due_commands().await;
+        let auction_aggregate = auction_repo.load(1).await.unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Ended)
+        );
+        assert!(scheduler.get_schedule().lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn scheduler_ends_auctions_started_after_recovery() {
+        let clock = ManualClock::new(Utc::now());
+        let ends_at = clock.now() + Duration::minutes(30);
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        let auction_repo = Arc::new(AuctionRepository::new(event_store));
+        let scheduler = DeadlineScheduler::new(
+            auction_repo.clone(),
+            Duration::minutes(5),
+            std::time::Duration::from_secs(1),
+        )
+        .with_clock(Arc::new(clock.clone()));
+        scheduler.recover().await;
+        assert!(scheduler.get_schedule().lock().unwrap().is_empty());
+
+        let auction_manager_service =
+            auction_manager_service.with_deadline_schedule(scheduler.get_schedule());
+        create_and_start_auction(&auction_manager_service, 1, auction_terms(ends_at)).await;
+        assert_eq!(
+            scheduler.get_schedule().lock().unwrap().get_ends_at(1),
+            Some(ends_at)
+        );
+
+        clock.advance(Duration::minutes(30));
+        scheduler.run_due_commands().await;
+        let auction_aggregate = auction_repo.load(1).await.unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Ended)
+        );
+    }
+
+    #[tokio::test]
+    async fn scheduler_settles_sealed_bid_auction_after_reveal_window() {
+        const NONCE: &str = "6c0e2f9a8b4d1735";
+        let clock = ManualClock::new(Utc::now());
+        let ends_at = clock.now() + Duration::minutes(30);
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        let auction_repo = Arc::new(AuctionRepository::new(event_store));
+        let scheduler = DeadlineScheduler::new(
+            auction_repo.clone(),
+            Duration::minutes(5),
+            std::time::Duration::from_secs(1),
+        )
+        .with_clock(Arc::new(clock.clone()));
+        let auction_manager_service =
+            auction_manager_service.with_deadline_schedule(scheduler.get_schedule());
+        let terms = AuctionTerms {
+            sealed_bid: Some(SealedBidTerms {
+                settlement: SealedBidSettlement::SecondPrice,
+                reveal_window: Duration::minutes(10),
+            }),
+            ..auction_terms(ends_at)
+        };
+        create_and_start_auction(&auction_manager_service, 1, terms).await;
+        for (bidder_id, value) in [(2, 40), (3, 25)] {
+            auction_manager_service
+                .submit_sealed_bid(
+                    1,
+                    bidder_id,
+                    commit(1, bidder_id, sgd(value), NONCE),
+                    CommandContext::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        // Deadline closes bidding and schedules the end of the reveal window
+        clock.advance(Duration::minutes(30));
+        scheduler.run_due_commands().await;
+        let auction_aggregate = auction_repo.load(1).await.unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_status(),
+            Some(AuctionStatus::Revealing)
+        );
+        assert_eq!(
+            scheduler.get_schedule().lock().unwrap().get_ends_at(1),
+            Some(clock.now() + Duration::minutes(10))
+        );
+        for (bidder_id, value) in [(2, 40), (3, 25)] {
+            auction_manager_service
+                .reveal_sealed_bid(
+                    1,
+                    bidder_id,
+                    sgd(value),
+                    String::from(NONCE),
+                    CommandContext::new(),
+                )
+                .await
+                .unwrap();
+        }
+
+        clock.advance(Duration::minutes(10));
+        scheduler.run_due_commands().await;
+        let auction_aggregate = auction_repo.load(1).await.unwrap();
+        let state = auction_aggregate.get_state();
+        assert_eq!(state.leading_bid().unwrap().get_bidder_id(), 2);
+        assert_eq!(
+            state.get_outcome(),
+            Some(AuctionOutcome::Sold { price: sgd(25) })
+        );
+        assert!(scheduler.get_schedule().lock().unwrap().is_empty());
+    }
+}
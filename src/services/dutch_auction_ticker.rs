@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use tokio::{task::JoinHandle, time};
+
+use crate::{
+    models::{
+        auction_aggregate::{AuctionCommand, AuctionId},
+        auction_error::AuctionError,
+        clock::{Clock, SystemClock},
+    },
+    repository::auction::AuctionRepository,
+};
+
+// Drops the price of a dutch auction on its schedule through the aggregate, so the price drops
+// are stored and published like any other event. The aggregate decides whether a drop is due,
+// the ticker only has to look often enough.
+pub struct DutchAuctionTicker {
+    auction_repo: Arc<AuctionRepository>,
+    auction_id: AuctionId,
+    poll_interval: std::time::Duration,
+    clock: Arc<dyn Clock>,
+}
+impl DutchAuctionTicker {
+    pub fn new(
+        auction_repo: Arc<AuctionRepository>,
+        auction_id: AuctionId,
+        poll_interval: std::time::Duration,
+    ) -> Self {
+        Self {
+            auction_repo,
+            auction_id,
+            poll_interval,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // Drops the price if it is due, returns whether the auction is still running
+    pub async fn tick(&self) -> bool {
+        let mut auction_aggregate = match self.auction_repo.load(self.auction_id).await {
+            Ok(auction_aggregate) => auction_aggregate.with_clock(Box::new(self.clock.clone())),
+            Err(error) => {
+                println!("Failed to load auction {}: {}", self.auction_id, error);
+                return true;
+            }
+        };
+        match auction_aggregate.execute(AuctionCommand::DropPrice) {
+            Ok(_) => {}
+            Err(AuctionError::PriceDropNotDue { .. }) => return true,
+            // Sold in between ticks
+            Err(AuctionError::AlreadyEnded) => return false,
+            Err(error) => {
+                println!(
+                    "Price of auction {} not dropped: {}",
+                    self.auction_id, error
+                );
+                return false;
+            }
+        }
+        // Left as it was when saving fails, to be dropped again on the next tick
+        let expected_version = auction_aggregate.get_committed_version();
+        if let Err(error) = self
+            .auction_repo
+            .commit_changes(&mut auction_aggregate, expected_version)
+            .await
+        {
+            println!("Failed to save auction {}: {}", self.auction_id, error);
+            return true;
+        }
+        let state = auction_aggregate.get_state();
+        if state.is_over() {
+            println!("Dutch auction {} ended unsold", self.auction_id);
+        }
+        !state.is_over()
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = time::interval(self.poll_interval);
+            loop {
+                interval.tick().await;
+                if !self.tick().await {
+                    break;
+                }
+            }
+            println!("Dutch auction ticker stopped");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use crate::{
+        models::{
+            auction::{AuctionOutcome, PriceDrop},
+            auction_aggregate::{AuctionAggregate, AuctionEventType, AuctionTerms},
+            clock::ManualClock,
+            price::{Currency, Price},
+        },
+        repository::{event_store::EventStore, in_memory_event_store::InMemoryEventStore},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn ticker_drops_price_until_floor() {
+        let clock = ManualClock::new(Utc::now());
+        let event_store = Arc::new(InMemoryEventStore::new());
+        let auction_repo = Arc::new(AuctionRepository::new(event_store.clone()));
+        let mut auction_aggregate =
+            AuctionAggregate::new(vec![]).with_clock(Box::new(clock.clone()));
+        auction_aggregate
+            .execute(AuctionCommand::CreateAuction {
+                auction_id: 1,
+                seller_id: 1,
+                terms: AuctionTerms {
+                    asking_price: Some(Price::new(Currency::SGD, 30)),
+                    price_drop: Some(PriceDrop {
+                        step: 10,
+                        floor_price: 20,
+                        interval: Duration::minutes(1),
+                    }),
+                    ..AuctionTerms::default()
+                },
+            })
+            .unwrap();
+        auction_aggregate
+            .execute(AuctionCommand::StartAuction)
+            .unwrap();
+        auction_repo
+            .commit_changes(&mut auction_aggregate, 0)
+            .await
+            .unwrap();
+        let ticker = DutchAuctionTicker::new(
+            auction_repo.clone(),
+            1,
+            std::time::Duration::from_millis(10),
+        )
+        .with_clock(Arc::new(clock.clone()));
+
+        // Starting price holds for a full interval
+        assert!(ticker.tick().await);
+        assert_eq!(auction_repo.load(1).await.unwrap().get_version(), 2);
+
+        clock.advance(Duration::minutes(1));
+        assert!(ticker.tick().await);
+        clock.advance(Duration::minutes(1));
+        ticker.spawn().await.unwrap();
+
+        let events = event_store.load_events_after(1, 2).await.unwrap();
+        let event_types: Vec<AuctionEventType> =
+            events.iter().map(|event| event.get_event_type()).collect();
+        assert_eq!(
+            event_types,
+            vec![
+                AuctionEventType::PriceDropped,
+                AuctionEventType::AuctionEnded
+            ]
+        );
+        assert_eq!(events[0].get_price(), Some(Price::new(Currency::SGD, 20)));
+        let auction_aggregate = auction_repo.load(1).await.unwrap();
+        assert_eq!(
+            auction_aggregate.get_state().get_outcome(),
+            Some(AuctionOutcome::EndedWithoutSale)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use tokio::{task::JoinHandle, time};
+
+use crate::repository::event_store::{EventPosition, EventStore, EventStoreError, RecordedEvent};
+
+pub type SubscriberError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug)]
+pub enum SubscriptionError {
+    EventStore(EventStoreError),
+    // Subscriber failed to handle a batch, it is delivered again on the next catch up
+    Subscriber(SubscriberError),
+}
+impl From<EventStoreError> for SubscriptionError {
+    fn from(error: EventStoreError) -> Self {
+        Self::EventStore(error)
+    }
+}
+impl fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventStore(error) => write!(f, "{}", error),
+            Self::Subscriber(error) => write!(f, "subscriber failed: {}", error),
+        }
+    }
+}
+impl std::error::Error for SubscriptionError {}
+
+// Consumer of the auction event log. A batch can be delivered again when the subscriber stops
+// before its checkpoint is saved, so handling the same events twice must be harmless.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    async fn handle(&self, events: &[RecordedEvent]) -> Result<(), SubscriberError>;
+}
+
+// Feeds a named subscriber every event in the store in order, starting after its checkpoint.
+// The checkpoint is saved after each batch, so a subscriber that crashed resumes where it
+// stopped without missing any event.
+pub struct EventSubscription {
+    name: String,
+    event_store: Arc<dyn EventStore>,
+    subscriber: Arc<dyn EventSubscriber>,
+    batch_size: usize,
+    poll_interval: std::time::Duration,
+}
+impl EventSubscription {
+    pub fn new(
+        name: &str,
+        event_store: Arc<dyn EventStore>,
+        subscriber: Arc<dyn EventSubscriber>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            event_store,
+            subscriber,
+            batch_size: 100,
+            poll_interval: std::time::Duration::from_secs(1),
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    // How often to look for events appended by other processes while caught up
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub async fn get_checkpoint(&self) -> Result<EventPosition, SubscriptionError> {
+        Ok(self.event_store.load_checkpoint(&self.name).await?)
+    }
+
+    // Delivers the events after the checkpoint batch by batch until none are left, returns
+    // how many
+    pub async fn catch_up(&self) -> Result<usize, SubscriptionError> {
+        let mut position = self.event_store.load_checkpoint(&self.name).await?;
+        let mut delivered = 0;
+        loop {
+            let events = self
+                .event_store
+                .load_all_events_after(position, self.batch_size)
+                .await?;
+            let Some(last_event) = events.last() else {
+                return Ok(delivered);
+            };
+            let last_position = last_event.get_position();
+            self.subscriber
+                .handle(&events)
+                .await
+                .map_err(SubscriptionError::Subscriber)?;
+            self.event_store
+                .save_checkpoint(&self.name, last_position)
+                .await?;
+            position = last_position;
+            delivered += events.len();
+            if events.len() < self.batch_size {
+                return Ok(delivered);
+            }
+        }
+    }
+
+    // Catches up over the stored events, then keeps delivering new ones as they are appended
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut appended = self.event_store.watch_appends();
+            loop {
+                // Appends from here on wake the subscription up again
+                appended.borrow_and_update();
+                match self.catch_up().await {
+                    Ok(0) => {}
+                    Ok(delivered) => {
+                        println!("Subscriber {} handled {} events", self.name, delivered)
+                    }
+                    Err(error) => println!("Subscriber {} failed: {}", self.name, error),
+                }
+                let _ = time::timeout(self.poll_interval, appended.changed()).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::sync::mpsc;
+
+    use crate::{
+        models::auction_aggregate::{AuctionTerms, CommandContext},
+        services::auction_manager::AuctionManagerService,
+        test_support::in_memory_auction_manager,
+    };
+
+    use super::*;
+
+    // Records the positions it was given, failing once when it reaches `fail_at`
+    #[derive(Default)]
+    struct RecordingSubscriber {
+        positions: Mutex<Vec<EventPosition>>,
+        fail_at: Mutex<Option<EventPosition>>,
+    }
+
+    #[async_trait]
+    impl EventSubscriber for RecordingSubscriber {
+        async fn handle(&self, events: &[RecordedEvent]) -> Result<(), SubscriberError> {
+            let mut fail_at = self.fail_at.lock().unwrap();
+            if events
+                .iter()
+                .any(|event| Some(event.get_position()) == *fail_at)
+            {
+                *fail_at = None;
+                return Err("subscriber crashed".into());
+            }
+            let mut positions = self.positions.lock().unwrap();
+            positions.extend(events.iter().map(|event| event.get_position()));
+            Ok(())
+        }
+    }
+
+    async fn create_auctions(
+        auction_manager_service: &AuctionManagerService,
+        auction_ids: std::ops::Range<u32>,
+    ) {
+        for auction_id in auction_ids {
+            auction_manager_service
+                .create_auction(
+                    auction_id,
+                    1,
+                    AuctionTerms::default(),
+                    CommandContext::new(),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn resumes_from_checkpoint_after_a_failed_batch() {
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        create_auctions(&auction_manager_service, 1..6).await;
+        let subscriber = Arc::new(RecordingSubscriber {
+            fail_at: Mutex::new(Some(4)),
+            ..RecordingSubscriber::default()
+        });
+        let subscription =
+            EventSubscription::new("recorder", event_store, subscriber.clone()).with_batch_size(2);
+
+        assert!(subscription.catch_up().await.is_err());
+        assert_eq!(subscription.get_checkpoint().await.unwrap(), 2);
+        assert_eq!(subscription.catch_up().await.unwrap(), 3);
+        assert_eq!(subscription.get_checkpoint().await.unwrap(), 5);
+        assert_eq!(*subscriber.positions.lock().unwrap(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(subscription.catch_up().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn delivers_live_events_after_catching_up() {
+        struct ForwardingSubscriber {
+            positions_tx: mpsc::UnboundedSender<EventPosition>,
+        }
+        #[async_trait]
+        impl EventSubscriber for ForwardingSubscriber {
+            async fn handle(&self, events: &[RecordedEvent]) -> Result<(), SubscriberError> {
+                for event in events {
+                    self.positions_tx.send(event.get_position())?;
+                }
+                Ok(())
+            }
+        }
+
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        create_auctions(&auction_manager_service, 1..3).await;
+        let (positions_tx, mut positions_rx) = mpsc::unbounded_channel();
+        // Polling alone would not pick up the live event within the test
+        let handle = EventSubscription::new(
+            "forwarder",
+            event_store,
+            Arc::new(ForwardingSubscriber { positions_tx }),
+        )
+        .with_poll_interval(std::time::Duration::from_secs(3600))
+        .spawn();
+
+        assert_eq!(positions_rx.recv().await, Some(1));
+        assert_eq!(positions_rx.recv().await, Some(2));
+        create_auctions(&auction_manager_service, 3..4).await;
+        let live_position =
+            time::timeout(std::time::Duration::from_secs(1), positions_rx.recv()).await;
+        assert_eq!(live_position.unwrap(), Some(3));
+        handle.abort();
+    }
+}
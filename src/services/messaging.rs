@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use tokio::sync::{broadcast, oneshot};
 use tokio_tungstenite::tungstenite::Message;
 
-use crate::dtos::message::MessageDto;
+use crate::{dtos::message::MessageDto, models::user::UserId};
 
 type Responder<T> = oneshot::Sender<T>;
 #[derive(Debug)]
@@ -15,7 +15,6 @@ pub enum Command {
     },
 }
 
-type UserId = u32;
 type ChatId = u32;
 type Channel = (broadcast::Sender<Message>, broadcast::Receiver<Message>);
 
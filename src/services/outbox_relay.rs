@@ -0,0 +1,229 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::auction_aggregate::AuctionEvent,
+    repository::event_store::{EventStore, EventStoreError},
+};
+
+// Number of recent event ids a receiver remembers to drop redelivered events
+const SEEN_EVENT_IDS: usize = 1024;
+
+// Auction events published to an in-process subscriber. The relay delivers at least once, an
+// event it delivers again is dropped here by its id.
+pub struct AuctionEventReceiver {
+    events_rx: UnboundedReceiver<AuctionEvent>,
+    seen: HashSet<Uuid>,
+    seen_order: VecDeque<Uuid>,
+}
+impl AuctionEventReceiver {
+    fn new(events_rx: UnboundedReceiver<AuctionEvent>) -> Self {
+        Self {
+            events_rx,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+        }
+    }
+
+    // Next event not received before, none once the relay is gone
+    pub async fn recv(&mut self) -> Option<AuctionEvent> {
+        loop {
+            let event = self.events_rx.recv().await?;
+            let event_id = event.get_metadata().get_event_id();
+            if !self.seen.insert(event_id) {
+                println!("Dropping redelivered auction event {}", event_id);
+                continue;
+            }
+            self.seen_order.push_back(event_id);
+            if self.seen_order.len() > SEEN_EVENT_IDS {
+                if let Some(oldest) = self.seen_order.pop_front() {
+                    self.seen.remove(&oldest);
+                }
+            }
+            return Some(event);
+        }
+    }
+}
+
+// Publishes the events in the outbox to in-process subscribers such as the websocket layer and
+// notifications. Events are only marked published after every subscriber was handed them, so a
+// relay that stops in between publishes them again.
+pub struct OutboxRelay {
+    event_store: Arc<dyn EventStore>,
+    subscribers: Arc<Mutex<Vec<UnboundedSender<AuctionEvent>>>>,
+    batch_size: usize,
+    poll_interval: std::time::Duration,
+}
+impl OutboxRelay {
+    pub fn new(event_store: Arc<dyn EventStore>) -> Self {
+        Self {
+            event_store,
+            subscribers: Arc::new(Mutex::new(vec![])),
+            batch_size: 100,
+            poll_interval: std::time::Duration::from_secs(1),
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    // How often to look for events appended by other processes
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn subscribe(&self) -> AuctionEventReceiver {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        self.subscribers.lock().unwrap().push(events_tx);
+        AuctionEventReceiver::new(events_rx)
+    }
+
+    // Hands the events to every subscriber, forgetting the ones that went away
+    fn publish(&self, events: &[AuctionEvent]) {
+        self.subscribers.lock().unwrap().retain(|events_tx| {
+            events
+                .iter()
+                .all(|event| events_tx.send(event.clone()).is_ok())
+        });
+    }
+
+    // Publishes the outbox batch by batch until it is empty, returns how many events
+    pub async fn relay(&self) -> Result<usize, EventStoreError> {
+        let mut published = 0;
+        loop {
+            let events = self
+                .event_store
+                .load_unpublished_events(self.batch_size)
+                .await?;
+            if events.is_empty() {
+                return Ok(published);
+            }
+            self.publish(&events);
+            let event_ids: Vec<Uuid> = events
+                .iter()
+                .map(|event| event.get_metadata().get_event_id())
+                .collect();
+            self.event_store.mark_published(&event_ids).await?;
+            published += events.len();
+            if events.len() < self.batch_size {
+                return Ok(published);
+            }
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut appended = self.event_store.watch_appends();
+            loop {
+                appended.borrow_and_update();
+                if let Err(error) = self.relay().await {
+                    println!("Failed to relay auction events: {}", error);
+                }
+                let _ = time::timeout(self.poll_interval, appended.changed()).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            auction_aggregate::{AuctionCommand, AuctionEventType, AuctionTerms, CommandContext},
+            bid::Bid,
+            bidding_rules::{BiddingRulesConfig, RuleSpec},
+            price::Currency,
+        },
+        repository::auction::AuctionRepository,
+        test_support::{create_and_start_auction, in_memory_auction_manager, sgd},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn relays_committed_events_once_to_every_subscriber() {
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        let relay = OutboxRelay::new(event_store.clone()).with_batch_size(1);
+        let mut websocket_rx = relay.subscribe();
+        let mut notifications_rx = relay.subscribe();
+        create_and_start_auction(&auction_manager_service, 1, AuctionTerms::default()).await;
+
+        assert_eq!(relay.relay().await.unwrap(), 2);
+        assert_eq!(relay.relay().await.unwrap(), 0);
+        let events = event_store.load_events_after(1, 0).await.unwrap();
+        for events_rx in [&mut websocket_rx, &mut notifications_rx] {
+            assert_eq!(events_rx.recv().await.as_ref(), Some(&events[0]));
+            assert_eq!(events_rx.recv().await.as_ref(), Some(&events[1]));
+        }
+
+        // Events published again after a relay stopped before marking them are dropped
+        relay.publish(&events);
+        auction_manager_service
+            .place_bid_for_auction(1, Bid::new(2, sgd(10)), CommandContext::new())
+            .await
+            .unwrap();
+        relay.relay().await.unwrap();
+        let next_event = websocket_rx.recv().await.unwrap();
+        assert_eq!(next_event.get_event_id(), 3);
+    }
+
+    #[tokio::test]
+    async fn subscribers_never_see_proxy_maximum_reserve_price_or_bidding_rules() {
+        let (event_store, auction_manager_service) = in_memory_auction_manager();
+        let auction_repo = AuctionRepository::new(event_store.clone());
+        let relay = OutboxRelay::new(event_store.clone());
+        let mut websocket_rx = relay.subscribe();
+        // Who may bid is for the auction alone
+        let bidding_rules = BiddingRulesConfig::default_for(Currency::SGD, 1)
+            .with_rule(RuleSpec::BidderAllowList {
+                bidder_ids: vec![2, 3],
+            })
+            .unwrap();
+        let terms = AuctionTerms {
+            opening_bid_price: Some(sgd(10)),
+            reserve_price: Some(80),
+            bidding_rules: Some(bidding_rules),
+            ..AuctionTerms::default()
+        };
+        create_and_start_auction(&auction_manager_service, 1, terms).await;
+        let mut auction_aggregate = auction_repo.load(1).await.unwrap();
+        auction_aggregate
+            .execute(AuctionCommand::PlaceProxyBid {
+                bid: Bid::new(2, sgd(50)),
+            })
+            .unwrap();
+        auction_repo
+            .commit_changes(&mut auction_aggregate, 2)
+            .await
+            .unwrap();
+
+        // Proxy bid registered and the opening bid it made for the bidder
+        assert_eq!(event_store.load_events_after(1, 0).await.unwrap().len(), 5);
+        assert_eq!(relay.relay().await.unwrap(), 4);
+        drop(relay);
+        let mut events = vec![];
+        while let Some(event) = websocket_rx.recv().await {
+            events.push(event);
+        }
+        assert_eq!(events.len(), 4);
+        for event in &events {
+            assert_ne!(event.get_event_type(), AuctionEventType::ProxyBidRegistered);
+            assert!(event.get_bid().is_none_or(|bid| bid.price.value != 50));
+            let json = serde_json::to_value(event).unwrap();
+            assert!(json["reserve_price"].is_null());
+            assert!(json["bidding_rules"].is_null());
+        }
+    }
+}
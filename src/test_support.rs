@@ -0,0 +1,44 @@
+// Fixtures shared by the test modules
+use std::sync::Arc;
+
+use crate::{
+    models::{
+        auction_aggregate::{AuctionId, AuctionTerms, CommandContext},
+        price::{Currency, Price},
+    },
+    repository::{
+        auction::AuctionRepository, in_memory_event_store::InMemoryEventStore,
+        in_memory_moderation_record_store::InMemoryModerationRecordStore,
+    },
+    services::auction_manager::AuctionManagerService,
+};
+
+pub fn sgd(value: u32) -> Price {
+    Price::new(Currency::SGD, value)
+}
+
+// Auction manager over an in-memory event store, which is handed back for tests to read from
+pub fn in_memory_auction_manager() -> (Arc<InMemoryEventStore>, AuctionManagerService) {
+    let event_store = Arc::new(InMemoryEventStore::new());
+    let auction_manager_service = AuctionManagerService::new(
+        AuctionRepository::new(event_store.clone()),
+        Arc::new(InMemoryModerationRecordStore::new()),
+    );
+    (event_store, auction_manager_service)
+}
+
+// Creates the auction for seller 1 and opens it for bidding
+pub async fn create_and_start_auction(
+    auction_manager_service: &AuctionManagerService,
+    auction_id: AuctionId,
+    terms: AuctionTerms,
+) {
+    auction_manager_service
+        .create_auction(auction_id, 1, terms, CommandContext::new())
+        .await
+        .unwrap();
+    auction_manager_service
+        .start_auction(auction_id, CommandContext::new())
+        .await
+        .unwrap();
+}